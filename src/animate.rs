@@ -0,0 +1,219 @@
+//! Step-by-step simulations redrawn in the terminal with ANSI colours.
+//!
+//! Enabled with `--animate`. `--fps <n>` sets the frame rate, `--every <n>`
+//! only draws every n-th step and `--step` waits for Enter before each frame.
+//! While stepping, a number skips that many steps, `c` plays on and `q` stops
+//! the animation while the computation carries on. Frames go to stderr so the
+//! answers on stdout stay clean.
+use crate::cli;
+use crate::render::Rgb;
+use anyhow::{Result, bail};
+use glam::IVec2;
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const CLEAR: &str = "\x1b[2J";
+const HOME: &str = "\x1b[H";
+const RESET: &str = "\x1b[0m";
+
+/// Character grid with a colour per cell, blank cells are spaces.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: usize,
+    cells: Vec<Option<(char, Rgb)>>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            cells: vec![None; width * height],
+        }
+    }
+    /// Draws `glyph` at `p`, points outside the canvas are ignored.
+    pub fn set(&mut self, p: IVec2, glyph: char, colour: Rgb) {
+        if p.x >= 0
+            && (p.x as usize) < self.width
+            && p.y >= 0
+            && let Some(cell) = self.cells.get_mut(p.y as usize * self.width + p.x as usize)
+        {
+            *cell = Some((glyph, colour));
+        }
+    }
+    /// Rows with 24-bit colour escapes, a colour is only emitted when it changes.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells.chunks(self.width.max(1)) {
+            let mut current = None;
+            for cell in row {
+                match cell {
+                    Some((glyph, colour)) => {
+                        if current != Some(*colour) {
+                            let [r, g, b] = colour;
+                            let _ = write!(out, "\x1b[38;2;{r};{g};{b}m");
+                            current = Some(*colour);
+                        }
+                        out.push(*glyph);
+                    }
+                    None => out.push(' '),
+                }
+            }
+            out.push_str(RESET);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    Next,
+    Skip(usize),
+    Play,
+    Stop,
+}
+
+impl Control {
+    fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "" => Some(Control::Next),
+            "c" => Some(Control::Play),
+            "q" => Some(Control::Stop),
+            n => n.parse().ok().map(Control::Skip),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Animation {
+    delay: Duration,
+    every: usize,
+    stepping: bool,
+    steps: usize,
+    skip: usize,
+    stopped: bool,
+    started: bool,
+}
+
+impl Animation {
+    pub fn new(fps: f64, every: usize, stepping: bool) -> Self {
+        Self {
+            delay: Duration::from_secs_f64(1.0 / fps),
+            every: every.max(1),
+            stepping,
+            steps: 0,
+            skip: 0,
+            stopped: false,
+            started: false,
+        }
+    }
+    /// Animation settings from the command line, `None` without `--animate`.
+    pub fn from_args() -> Result<Option<Self>> {
+        Self::parse(&cli::args())
+    }
+    fn parse(args: &[String]) -> Result<Option<Self>> {
+        if !cli::flag(args, "--animate") {
+            return Ok(None);
+        }
+        let fps: f64 = cli::value(args, "--fps")?.unwrap_or(10.0);
+        if !(fps > 0.0 && fps.is_finite()) {
+            bail!("--fps must be positive, got {fps}");
+        }
+        let every = cli::value(args, "--every")?.unwrap_or(1);
+        let stepping = cli::flag(args, "--step");
+        Ok(Some(Self::new(fps, every, stepping)))
+    }
+    /// Counts one simulation step and shows it unless it is skipped, `draw` is
+    /// only called for frames that are shown.
+    pub fn frame(&mut self, title: &str, draw: impl FnOnce() -> Canvas) -> Result<()> {
+        self.steps += 1;
+        if self.stopped || !(self.steps - 1).is_multiple_of(self.every) {
+            return Ok(());
+        }
+        if self.skip > 0 {
+            self.skip -= 1;
+            return Ok(());
+        }
+        let mut err = std::io::stderr().lock();
+        if !self.started {
+            write!(err, "{HIDE_CURSOR}{CLEAR}")?;
+            self.started = true;
+        }
+        write!(err, "{HOME}{title}\x1b[K\n{}", draw().to_ansi())?;
+        err.flush()?;
+        drop(err);
+        if self.stepping {
+            self.wait_for_control()
+        } else {
+            std::thread::sleep(self.delay);
+            Ok(())
+        }
+    }
+    fn wait_for_control(&mut self) -> Result<()> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if std::io::stdin().lock().read_line(&mut line)? == 0 {
+                // no more input, keep playing
+                self.stepping = false;
+                return Ok(());
+            }
+            match Control::parse(&line) {
+                Some(Control::Next) => {}
+                Some(Control::Skip(n)) => self.skip = n,
+                Some(Control::Play) => self.stepping = false,
+                Some(Control::Stop) => self.stopped = true,
+                None => continue,
+            }
+            return Ok(());
+        }
+    }
+}
+
+impl Drop for Animation {
+    fn drop(&mut self) {
+        if self.started {
+            eprint!("{SHOW_CURSOR}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn options_and_controls() -> Result<()> {
+        assert!(Animation::parse(&args("--render out.png"))?.is_none());
+        let animation = Animation::parse(&args("--animate --fps 4 --every 3 --step"))?.unwrap();
+        assert_eq!(animation.delay, Duration::from_millis(250));
+        assert_eq!((animation.every, animation.stepping), (3, true));
+        assert!(Animation::parse(&args("--animate --fps 0")).is_err());
+        assert!(Animation::parse(&args("--animate --every")).is_err());
+        assert_eq!(Control::parse("\n"), Some(Control::Next));
+        assert_eq!(Control::parse("12\n"), Some(Control::Skip(12)));
+        assert_eq!(Control::parse("q"), Some(Control::Stop));
+        assert_eq!(Control::parse("x"), None);
+        Ok(())
+    }
+    #[test]
+    fn canvas_colours_runs_once() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.set(IVec2::new(0, 0), '@', [1, 2, 3]);
+        canvas.set(IVec2::new(1, 0), '@', [1, 2, 3]);
+        canvas.set(IVec2::new(2, 1), '|', [9, 9, 9]);
+        canvas.set(IVec2::new(3, 0), 'x', [9, 9, 9]);
+        canvas.set(IVec2::new(0, 5), 'x', [9, 9, 9]);
+        assert_eq!(
+            canvas.to_ansi(),
+            "\x1b[38;2;1;2;3m@@ \x1b[0m\n  \x1b[38;2;9;9;9m|\x1b[0m\n"
+        );
+    }
+}
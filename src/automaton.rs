@@ -0,0 +1,147 @@
+//! Erosion automaton: live cells die in waves while a rule on their live
+//! neighbour count says so, dead cells never come back.
+use glam::IVec2;
+use std::collections::HashSet;
+
+pub const VON_NEUMANN: [IVec2; 4] = [IVec2::NEG_Y, IVec2::NEG_X, IVec2::X, IVec2::Y];
+pub const MOORE: [IVec2; 8] = [
+    IVec2::new(-1, -1),
+    IVec2::new(0, -1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 0),
+    IVec2::new(1, 0),
+    IVec2::new(-1, 1),
+    IVec2::new(0, 1),
+    IVec2::new(1, 1),
+];
+
+/// Runs wave by wave, as an iterator yielding the cells removed in each wave.
+/// Only neighbours of cells removed in the previous wave are re-examined.
+#[derive(Debug, Clone)]
+pub struct Erosion<R> {
+    live: HashSet<IVec2>,
+    neighbourhood: Vec<IVec2>,
+    removes: R,
+    worklist: HashSet<IVec2>,
+}
+
+impl<R: Fn(usize) -> bool> Erosion<R> {
+    /// `removes` gets the live neighbour count of a cell and decides whether it dies.
+    pub fn new(live: impl IntoIterator<Item = IVec2>, neighbourhood: &[IVec2], removes: R) -> Self {
+        let live: HashSet<IVec2> = live.into_iter().collect();
+        Self {
+            worklist: live.clone(),
+            live,
+            neighbourhood: neighbourhood.to_vec(),
+            removes,
+        }
+    }
+    /// Live cells from rows of flags, `x` is the column and `y` the row.
+    pub fn from_grid(rows: &[Vec<bool>], neighbourhood: &[IVec2], removes: R) -> Self {
+        let live = rows.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, alive)| **alive)
+                .map(move |(x, _)| IVec2::new(x as i32, y as i32))
+        });
+        Self::new(live, neighbourhood, removes)
+    }
+    pub fn live(&self) -> &HashSet<IVec2> {
+        &self.live
+    }
+    pub fn live_neighbours(&self, cell: IVec2) -> usize {
+        self.neighbourhood
+            .iter()
+            .filter(|offset| self.live.contains(&(cell + **offset)))
+            .count()
+    }
+    /// Cells the next wave removes, sorted by row then column.
+    pub fn doomed(&self) -> Vec<IVec2> {
+        let mut doomed: Vec<IVec2> = self
+            .worklist
+            .iter()
+            .copied()
+            .filter(|cell| self.live.contains(cell))
+            .filter(|cell| (self.removes)(self.live_neighbours(*cell)))
+            .collect();
+        doomed.sort_by_key(|cell| (cell.y, cell.x));
+        doomed
+    }
+    /// Removes one wave, `None` once nothing changes anymore.
+    pub fn step(&mut self) -> Option<Vec<IVec2>> {
+        let wave = self.doomed();
+        if wave.is_empty() {
+            self.worklist.clear();
+            return None;
+        }
+        for cell in &wave {
+            self.live.remove(cell);
+        }
+        self.worklist = wave
+            .iter()
+            .flat_map(|cell| self.neighbourhood.iter().map(move |offset| cell + offset))
+            .filter(|cell| self.live.contains(cell))
+            .collect();
+        Some(wave)
+    }
+}
+
+impl<R: Fn(usize) -> bool> Iterator for Erosion<R> {
+    type Item = Vec<IVec2>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plus_sign_erodes_from_the_tips() {
+        // tips have one neighbour, the centre four, the arms two
+        let plus = [
+            (2, 0),
+            (2, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+            (3, 2),
+            (4, 2),
+            (2, 3),
+            (2, 4),
+        ]
+        .map(|(x, y)| IVec2::new(x, y));
+        let waves: Vec<_> = Erosion::new(plus, &VON_NEUMANN, |n| n < 2).collect();
+        assert_eq!(
+            waves,
+            vec![
+                vec![
+                    IVec2::new(2, 0),
+                    IVec2::new(0, 2),
+                    IVec2::new(4, 2),
+                    IVec2::new(2, 4)
+                ],
+                vec![
+                    IVec2::new(2, 1),
+                    IVec2::new(1, 2),
+                    IVec2::new(3, 2),
+                    IVec2::new(2, 3)
+                ],
+                vec![IVec2::new(2, 2)],
+            ]
+        );
+    }
+    #[test]
+    fn stable_core_survives() {
+        let rows = vec![vec![true; 4]; 4];
+        let mut erosion = Erosion::from_grid(&rows, &MOORE, |n| n < 4);
+        // only the corners have three neighbours
+        assert_eq!(erosion.step().map(|wave| wave.len()), Some(4));
+        assert_eq!(erosion.live().len(), 12);
+        assert_eq!(erosion.live_neighbours(IVec2::new(1, 1)), 7);
+        assert_eq!(erosion.step(), None);
+        assert_eq!(erosion.live().len(), 12);
+    }
+}
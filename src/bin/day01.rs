@@ -1,92 +1,372 @@
-use anyhow::{Error, Ok, Result, anyhow};
+use anyhow::{Context, Ok, Result, anyhow, bail};
+use aoc_2025::cli;
+use aoc_2025::dial::Dial;
+use aoc_2025::parsers::{self, IResult, lines, unsigned};
+use aoc_2025::{trace, traced};
+use itertools::Itertools;
+use nom::Parser;
+use nom::branch::alt;
+use nom::character::complete::char;
+use nom::combinator::opt;
+use nom::sequence::terminated;
+use std::fmt::Display;
 use std::fs::read_to_string;
-use std::str::FromStr;
+use std::ops::RangeInclusive;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Dir {
     Left,
     Right,
 }
 
-impl TryFrom<char> for Dir {
-    type Error = Error;
+#[derive(Debug, Clone, Copy)]
+struct Rotation {
+    /// Lock wheel counted from 1, `None` turns every wheel together.
+    wheel: Option<usize>,
+    dir: Dir,
+    distance: i64,
+}
 
-    fn try_from(value: char) -> std::result::Result<Self, Self::Error> {
-        match value {
-            'L' => Ok(Dir::Left),
-            'R' => Ok(Dir::Right),
-            _ => Err(anyhow!("unknown direction")),
+impl Rotation {
+    /// Clicks to turn the dial by, left turns towards lower numbers.
+    fn amount(&self) -> i64 {
+        match self.dir {
+            Dir::Left => -self.distance,
+            Dir::Right => self.distance,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Rotation {
-    dir: Dir,
-    distance: i16,
+/// Dial size, start position and the letters of left and right turns, set
+/// with `--size`, `--start` and `--letters` on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Config {
+    size: i64,
+    start: i64,
+    left: char,
+    right: char,
 }
 
-impl Rotation {
-    fn count_zero_passes(&self, pos: i16) -> (i16, i16) {
-        let full_circles = (self.distance / 100).abs();
-        let dist_rem = self.distance % 100;
-        let clockwise_sign = match self.dir {
-            Dir::Left => -1,
-            Dir::Right => 1,
-        };
-        let next_pos = pos + dist_rem * clockwise_sign;
-        let zero_pass = if (next_pos > 0 && next_pos < 100) || pos == 0 {
-            0
-        } else {
-            1
-        };
-        ((100 + next_pos) % 100, full_circles + zero_pass)
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            size: 100,
+            start: 50,
+            left: 'L',
+            right: 'R',
+        }
     }
 }
 
-impl FromStr for Rotation {
-    type Err = Error;
+impl Config {
+    fn from_args(args: &[String]) -> Result<Self> {
+        let default = Self::default();
+        let (left, right) = match cli::value::<String>(args, "--letters")? {
+            None => (default.left, default.right),
+            Some(letters) => letters
+                .chars()
+                .collect_tuple()
+                .filter(|(left, right)| left != right)
+                .ok_or(anyhow!(
+                    "--letters needs two different letters, got {letters}"
+                ))?,
+        };
+        Ok(Self {
+            size: cli::value(args, "--size")?.unwrap_or(default.size),
+            start: cli::value(args, "--start")?.unwrap_or(default.start),
+            left,
+            right,
+        })
+    }
+    fn dial(&self) -> Result<Dial> {
+        Dial::new(self.size, self.start)
+    }
+}
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut line = s.chars();
-        let dir = line.next().ok_or(anyhow!("empty line"))?.try_into()?;
-        let distance = line.as_str().parse()?;
-        Ok(Rotation { dir, distance })
+fn rotation(config: &Config) -> impl FnMut(&str) -> IResult<'_, Rotation> {
+    let (left, right) = (config.left, config.right);
+    move |input| {
+        let dir = alt((
+            char(left).map(|_| Dir::Left),
+            char(right).map(|_| Dir::Right),
+        ));
+        let wheel = opt(terminated(unsigned, char(':')));
+        (wheel, dir, unsigned)
+            .map(|(wheel, dir, distance)| Rotation {
+                wheel,
+                dir,
+                distance,
+            })
+            .parse(input)
     }
 }
 
+fn parse(input: &str, config: &Config) -> Result<Vec<Rotation>> {
+    parsers::run(lines(rotation(config)), input)
+}
+
 fn main() -> Result<()> {
+    trace::init();
+    let config = Config::from_args(&cli::args())?;
     let input = read_to_string("inputs/day01-input1.txt")?;
-    let answer = count_dial_finished_zero(input.trim(), 50)?;
+    let rotations = traced!("parse", parse(input.trim(), &config))?;
+    if let Some(sizes) = wheel_sizes(&cli::args())? {
+        let lock = traced!("lock", run_lock(&rotations, &sizes, config.start))?;
+        println!("all wheels at zero: {}", lock.all_at_zero);
+        for (wheel, passed) in lock.zeros_passed.iter().enumerate() {
+            println!("wheel {} passed zero: {passed}", wheel + 1);
+        }
+        return Ok(());
+    }
+    if let Some((count, observed)) = observed_count(&cli::args())? {
+        let starts = starts_for_count(&rotations, config.size, count, observed)?;
+        println!("starts giving {observed} zeros: {starts:?}");
+        return Ok(());
+    }
+    if cli::flag(&cli::args(), "--trace") {
+        for event in rotation_events(&rotations, &config)? {
+            println!("{event}");
+        }
+    }
+    let answer = traced!("part1", count_dial_finished_zero(&rotations, &config))?;
     println!("part 1 answer is: {answer}");
-    let answer = count_dials_crosses_zero(input.trim(), 50)?;
+    let answer = traced!("part2", count_dials_crosses_zero(&rotations, &config))?;
     println!("part 2 answer is: {answer}");
     Ok(())
 }
 
-fn count_dial_finished_zero(input: &str, start_pos: i16) -> Result<u32> {
-    let (_last_pos, zeros) = input.lines().map(Rotation::from_str).try_fold(
-        (start_pos, 0),
-        |(cur_pos, zeros), rot_parsed| {
-            let rot = rot_parsed?;
-            let new_pos = match rot.dir {
-                Dir::Left => (cur_pos - rot.distance + 100) % 100,
-                Dir::Right => (cur_pos + rot.distance) % 100,
+/// What one rotation did to the dial.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RotationEvent {
+    index: usize,
+    dir: Dir,
+    distance: i64,
+    start: i64,
+    end: i64,
+    /// Clicks that pointed the dial at zero, the final one included.
+    zeros: u64,
+    first_zero: Option<i64>,
+    size: i64,
+}
+
+impl RotationEvent {
+    /// Clicks into the rotation at which the dial pointed at zero.
+    fn zero_clicks(&self) -> impl Iterator<Item = i64> + use<> {
+        let (zeros, size) = (self.zeros as i64, self.size);
+        self.first_zero
+            .into_iter()
+            .flat_map(move |first| (0..zeros).map(move |k| first + k * size))
+    }
+}
+
+impl Display for RotationEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const SHOWN: usize = 5;
+        let dir = match self.dir {
+            Dir::Left => "left",
+            Dir::Right => "right",
+        };
+        write!(
+            f,
+            "#{} {dir} {}: {} -> {}, zero {} times",
+            self.index, self.distance, self.start, self.end, self.zeros
+        )?;
+        if self.zeros > 0 {
+            let clicks = self.zero_clicks().take(SHOWN).join(", ");
+            let more = if self.zeros > SHOWN as u64 {
+                ", ..."
+            } else {
+                ""
             };
-            Ok((new_pos, if new_pos == 0 { zeros + 1 } else { zeros }))
-        },
-    )?;
-    Ok(zeros)
+            write!(f, " at clicks {clicks}{more}")?;
+        }
+        std::fmt::Result::Ok(())
+    }
+}
+
+/// Turns the dial through all rotations, one event per rotation.
+fn rotation_events(
+    rotations: &[Rotation],
+    config: &Config,
+) -> Result<impl Iterator<Item = RotationEvent>> {
+    single_wheel(rotations)?;
+    let mut dial = config.dial()?;
+    Ok(rotations.iter().enumerate().map(move |(index, rot)| {
+        let start = dial.position();
+        let first_zero = dial.first_zero(rot.amount());
+        let zeros = dial.rotate(rot.amount());
+        RotationEvent {
+            index,
+            dir: rot.dir,
+            distance: rot.distance,
+            start,
+            end: dial.position(),
+            zeros,
+            first_zero,
+            size: dial.size(),
+        }
+    }))
+}
+
+fn count_dial_finished_zero(rotations: &[Rotation], config: &Config) -> Result<usize> {
+    Ok(rotation_events(rotations, config)?
+        .filter(|event| event.end == 0)
+        .count())
+}
+
+fn count_dials_crosses_zero(rotations: &[Rotation], config: &Config) -> Result<u64> {
+    Ok(rotation_events(rotations, config)?
+        .map(|event| event.zeros)
+        .sum())
+}
+
+fn single_wheel(rotations: &[Rotation]) -> Result<()> {
+    if let Some(wheel) = rotations
+        .iter()
+        .find_map(|rot| rot.wheel.filter(|w| *w != 1))
+    {
+        bail!("rotation of wheel {wheel} on a single dial, pass the lock with --wheels");
+    }
+    Ok(())
+}
+
+/// Which zeros a recording counted, the part 1 or the part 2 way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ZeroCount {
+    Finished,
+    Passed,
+}
+
+/// Zero count for every start position `0..size` in O(n + size).
+///
+/// A rotation ends at zero for exactly one start. Passing zero takes the full
+/// turns of a rotation plus one when its remaining clicks reach zero, which
+/// happens for a cyclic interval of starts, added up in a difference array.
+fn counts_by_start(rotations: &[Rotation], size: i64, count: ZeroCount) -> Result<Vec<u64>> {
+    single_wheel(rotations)?;
+    let len = usize::try_from(size)
+        .ok()
+        .filter(|len| *len > 0)
+        .ok_or(anyhow!("dial size must be positive, got {size}"))?;
+    let mut diff = vec![0_i64; len + 1];
+    // adds one for the starts at which the dial points at `positions` after
+    // `offset` clicks
+    let mut add_starts = |positions: RangeInclusive<i64>, offset: i64| {
+        let first = (positions.start() - offset).rem_euclid(size) as usize;
+        let end = first + positions.count();
+        diff[first] += 1;
+        if end <= len {
+            diff[end] -= 1;
+        } else {
+            diff[len] -= 1;
+            diff[0] += 1;
+            diff[end - len] -= 1;
+        }
+    };
+    let mut full_turns = 0;
+    // clicks turned so far modulo size
+    let mut offset = 0;
+    for rot in rotations {
+        if count == ZeroCount::Passed {
+            full_turns += (rot.distance / size) as u64;
+            let rest = rot.distance % size;
+            match rot.dir {
+                _ if rest == 0 => {}
+                Dir::Right => add_starts(size - rest..=size - 1, offset),
+                Dir::Left => add_starts(1..=rest, offset),
+            }
+        }
+        offset = (offset as i128 + rot.amount() as i128).rem_euclid(size as i128) as i64;
+        if count == ZeroCount::Finished {
+            add_starts(0..=0, offset);
+        }
+    }
+    let counts = diff[..len]
+        .iter()
+        .scan(0, |running, d| {
+            *running += d;
+            Some(*running as u64 + full_turns)
+        })
+        .collect();
+    Ok(counts)
+}
+
+/// Every start position that yields the `observed` zero count.
+fn starts_for_count(
+    rotations: &[Rotation],
+    size: i64,
+    count: ZeroCount,
+    observed: u64,
+) -> Result<Vec<i64>> {
+    Ok(counts_by_start(rotations, size, count)?
+        .into_iter()
+        .positions(|zeros| zeros == observed)
+        .map(|start| start as i64)
+        .collect())
+}
+
+/// Observed count given as `--observed <part>:<count>`, e.g. `--observed 2:6`.
+fn observed_count(args: &[String]) -> Result<Option<(ZeroCount, u64)>> {
+    let Some(observed) = cli::value::<String>(args, "--observed")? else {
+        return Ok(None);
+    };
+    let bad = || anyhow!("--observed needs <part>:<count>, got {observed}");
+    let (part, zeros) = observed.split_once(':').ok_or_else(bad)?;
+    let count = match part {
+        "1" => ZeroCount::Finished,
+        "2" => ZeroCount::Passed,
+        _ => return Err(bad()),
+    };
+    Ok(Some((count, zeros.parse().map_err(|_| bad())?)))
+}
+
+/// Sizes of the lock wheels, given as `--wheels 100,60,24`.
+fn wheel_sizes(args: &[String]) -> Result<Option<Vec<i64>>> {
+    let Some(sizes) = cli::value::<String>(args, "--wheels")? else {
+        return Ok(None);
+    };
+    let sizes = sizes
+        .split(',')
+        .map(|size| size.trim().parse())
+        .collect::<Result<Vec<i64>, _>>()
+        .with_context(|| format!("bad --wheels {sizes}"))?;
+    Ok(Some(sizes))
 }
 
-fn count_dials_crosses_zero(input: &str, start_pos: i16) -> Result<i16> {
-    Ok(input.lines().map(Rotation::from_str).try_fold(
-        (start_pos, 0),
-        |(cur_pos, zeros), rot_parsed| {
-            let (next_pos, cur_zeros) = rot_parsed?.count_zero_passes(cur_pos);
-            Ok((next_pos, zeros + cur_zeros))
-        },
-    )?.1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LockStats {
+    /// Rotations after which every wheel pointed at zero.
+    all_at_zero: usize,
+    zeros_passed: Vec<u64>,
+}
+
+/// Turns the wheels of a combination lock, all starting at `start`.
+fn run_lock(rotations: &[Rotation], sizes: &[i64], start: i64) -> Result<LockStats> {
+    if sizes.is_empty() {
+        bail!("a lock needs at least one wheel");
+    }
+    let mut wheels: Vec<Dial> = sizes
+        .iter()
+        .map(|size| Dial::new(*size, start))
+        .collect::<Result<_>>()?;
+    let mut stats = LockStats {
+        all_at_zero: 0,
+        zeros_passed: vec![0; wheels.len()],
+    };
+    for rot in rotations {
+        let turned = match rot.wheel {
+            None => 0..wheels.len(),
+            Some(wheel @ 1..) if wheel <= wheels.len() => wheel - 1..wheel,
+            Some(wheel) => bail!("no wheel {wheel} on a lock with {} wheels", wheels.len()),
+        };
+        for i in turned {
+            stats.zeros_passed[i] += wheels[i].rotate(rot.amount());
+        }
+        if wheels.iter().all(Dial::at_zero) {
+            stats.all_at_zero += 1;
+        }
+    }
+    Ok(stats)
 }
 
 #[cfg(test)]
@@ -107,12 +387,99 @@ L82
 "#;
     #[test]
     fn part1() -> Result<()> {
-        assert_eq!(count_dial_finished_zero(INPUT.trim(), 50)?, 3);
+        let config = Config::default();
+        let rotations = parse(INPUT.trim(), &config)?;
+        assert_eq!(count_dial_finished_zero(&rotations, &config)?, 3);
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        assert_eq!(count_dials_crosses_zero(INPUT.trim(), 50)?, 6);
+        let config = Config::default();
+        let rotations = parse(INPUT.trim(), &config)?;
+        assert_eq!(count_dials_crosses_zero(&rotations, &config)?, 6);
+        Ok(())
+    }
+    #[test]
+    fn events_record_every_zero() -> Result<()> {
+        let config = Config::default();
+        let rotations = parse(INPUT.trim(), &config)?;
+        let events: Vec<_> = rotation_events(&rotations, &config)?.collect();
+        assert_eq!(events.len(), 10);
+        assert_eq!((events[0].start, events[0].end), (50, 82));
+        assert_eq!(events[0].zero_clicks().collect_vec(), vec![50]);
+        assert_eq!(
+            events[2].to_string(),
+            "#2 right 48: 52 -> 0, zero 1 times at clicks 48"
+        );
+        let long = parse("R1050", &config)?;
+        let event = rotation_events(&long, &config)?
+            .next()
+            .expect("one rotation");
+        assert_eq!(
+            event.to_string(),
+            "#0 right 1050: 50 -> 0, zero 11 times at clicks 50, 150, 250, 350, 450, ..."
+        );
+        assert_eq!(event.zero_clicks().last(), Some(1050));
+        Ok(())
+    }
+    #[test]
+    fn counts_for_every_start() -> Result<()> {
+        let default = Config::default();
+        let example = parse(INPUT.trim(), &default)?;
+        let odd = parse("R7\nL23\nR1\nL1\nL5\nR100\nL3\nR12", &default)?;
+        for (rotations, size) in [(&example, 100), (&example, 7), (&odd, 5), (&odd, 1)] {
+            let finished = counts_by_start(rotations, size, ZeroCount::Finished)?;
+            let passed = counts_by_start(rotations, size, ZeroCount::Passed)?;
+            for start in 0..size {
+                let config = Config {
+                    size,
+                    start,
+                    ..default
+                };
+                let expected = count_dial_finished_zero(rotations, &config)? as u64;
+                assert_eq!(finished[start as usize], expected, "{size} {start}");
+                let expected = count_dials_crosses_zero(rotations, &config)?;
+                assert_eq!(passed[start as usize], expected, "{size} {start}");
+            }
+        }
+        assert!(starts_for_count(&example, 100, ZeroCount::Finished, 3)?.contains(&50));
+        assert!(starts_for_count(&example, 100, ZeroCount::Passed, 6)?.contains(&50));
+        let args = ["--observed", "2:6"].map(String::from);
+        assert_eq!(observed_count(&args)?, Some((ZeroCount::Passed, 6)));
+        Ok(())
+    }
+    #[test]
+    fn lock_wheels() -> Result<()> {
+        let config = Config::default();
+        let rotations = parse(INPUT.trim(), &config)?;
+        // one wheel turned by everything is the plain dial
+        let single = run_lock(&rotations, &[100], 50)?;
+        assert_eq!(single.all_at_zero, 3);
+        assert_eq!(single.zeros_passed, vec![6]);
+        let rotations = parse("1:L50\n2:R10\nL100\nR20\n1:L20\n2:R20", &config)?;
+        let lock = run_lock(&rotations, &[100, 60], 50)?;
+        assert_eq!(lock.zeros_passed, vec![1 + 1 + 1, 1 + 1 + 1]);
+        assert_eq!(lock.all_at_zero, 2);
+        assert!(run_lock(&rotations, &[100], 50).is_err());
+        assert!(rotation_events(&rotations, &config).is_err());
+        let args = ["--wheels", "100, 60"].map(String::from);
+        assert_eq!(wheel_sizes(&args)?, Some(vec![100, 60]));
+        Ok(())
+    }
+    #[test]
+    fn other_dials_and_letters() -> Result<()> {
+        let args: Vec<String> = ["--size", "10", "--start", "3", "--letters", "<>"]
+            .map(String::from)
+            .into();
+        let config = Config::from_args(&args)?;
+        assert_eq!((config.size, config.start), (10, 3));
+        let rotations = parse("<3\n>40000\n<7", &config)?;
+        assert_eq!(rotations[1].distance, 40000);
+        assert_eq!(count_dial_finished_zero(&rotations, &config)?, 2);
+        assert_eq!(count_dials_crosses_zero(&rotations, &config)?, 1 + 4000);
+        assert!(parse("L3", &config).is_err());
+        let same = ["--letters", "LL"].map(String::from);
+        assert!(Config::from_args(&same).is_err());
         Ok(())
     }
 }
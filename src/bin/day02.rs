@@ -1,43 +1,70 @@
+use anyhow::{Ok, Result, bail};
+use aoc_2025::cli;
+use aoc_2025::parsers::{self, comma_separated, range_radix};
+use aoc_2025::repdigits::{Digits, Matches, Policy, matching};
+use aoc_2025::{trace, traced};
 use std::fs::read_to_string;
 use std::ops::RangeInclusive;
-use anyhow::{Ok, Result, anyhow};
 
-fn parse(input: &str) -> Result<Vec<RangeInclusive<u64>>> {
-    input
-        .split(',')
-        .map(|range| {
-            range
-                .split_once('-')
-                .map(|(start, end)| start.parse().unwrap()..=end.parse().unwrap())
-                .ok_or(anyhow!("parse failed"))
-        })
-        .collect()
+/// Id base and fixed width, `--radix 16 --width 8` on the command line.
+fn digits_from_args(args: &[String]) -> Result<Digits> {
+    let radix = cli::value(args, "--radix")?.unwrap_or(10);
+    Digits::new(radix, cli::value(args, "--width")?)
 }
-fn is_invalid_id_part1(id: &u64) -> bool {
-    let id = id.to_string();
-    id.len().is_multiple_of(2) && id[..id.len() / 2] == id[id.len() / 2..]
-}
-fn is_invalid_id_part2(id: &u64) -> bool {
-    let id_str = id.to_string();
-    let double_id_str = id_str.repeat(2);
-    double_id_str[1..double_id_str.len()-1].contains(&id_str)
+
+/// Ranges reaching past the widest id are rejected rather than cut short.
+fn parse(input: &str, digits: Digits) -> Result<Vec<RangeInclusive<u64>>> {
+    let ranges = parsers::run(comma_separated(range_radix(digits.radix())), input)?;
+    if let Some(width) = digits.width()
+        && let Some(range) = ranges.iter().find(|range| *range.end() > digits.max_id())
+    {
+        bail!(
+            "range {}-{} has ids wider than {width} digits",
+            digits.format(*range.start()),
+            digits.format(*range.end()),
+        );
+    }
+    Ok(ranges)
 }
 
-fn invalid_ids_sum(ranges: &[RangeInclusive<u64>], pred: fn(&u64) -> bool) -> u64 {
-    ranges
-        .iter()
-        .flat_map(|range| {
-            range.clone().filter(pred)
-        })
-        .sum()
+/// Part 1 ids are a block repeated exactly twice, part 2 ids twice or more.
+const PART1: Policy = Policy::Exactly(2);
+const PART2: Policy = Policy::AtLeast(2);
+
+/// Invalid ids of all ranges, listed in range order if `list` is set.
+fn invalid_ids_sum(
+    ranges: &[RangeInclusive<u64>],
+    policy: &Policy,
+    digits: Digits,
+    list: bool,
+) -> Matches {
+    let mut total = Matches {
+        ids: list.then(Vec::new),
+        ..Matches::default()
+    };
+    for range in ranges {
+        total.merge(matching(*range.start(), *range.end(), policy, digits, list));
+    }
+    total
 }
 
 fn main() -> Result<()> {
+    trace::init();
+    let args = cli::args();
+    let digits = digits_from_args(&args)?;
     let input = read_to_string("inputs/day02-input1.txt")?;
-    let ranges = parse(input.trim())?;
-    let answer = invalid_ids_sum(&ranges, is_invalid_id_part1);
+    let ranges = traced!("parse", parse(input.trim(), digits))?;
+    if let Some(policy) = cli::value::<Policy>(&args, "--policy")? {
+        let matches = invalid_ids_sum(&ranges, &policy, digits, cli::flag(&args, "--list"));
+        for id in matches.ids.iter().flatten() {
+            println!("{}", digits.format(*id));
+        }
+        println!("{} invalid ids, sum {}", matches.count, matches.sum);
+        return Ok(());
+    }
+    let answer = traced!("part1", invalid_ids_sum(&ranges, &PART1, digits, false).sum);
     println!("part 1 answer is: {answer}");
-    let answer2 = invalid_ids_sum(&ranges, is_invalid_id_part2);
+    let answer2 = traced!("part2", invalid_ids_sum(&ranges, &PART2, digits, false).sum);
     println!("part 2 answer is: {answer2}");
     Ok(())
 }
@@ -45,19 +72,88 @@ fn main() -> Result<()> {
 mod tests {
     use super::*;
 
+    fn is_invalid_id_part1(id: u64, digits: Digits) -> bool {
+        let id = digits.format(id);
+        id.len().is_multiple_of(2) && id[..id.len() / 2] == id[id.len() / 2..]
+    }
+    fn is_invalid_id_part2(id: u64, digits: Digits) -> bool {
+        let id_str = digits.format(id);
+        let double_id_str = id_str.repeat(2);
+        double_id_str[1..double_id_str.len() - 1].contains(&id_str)
+    }
+    /// Ids that do not fit a fixed width are not ids at all.
+    fn fits(id: u64, digits: Digits) -> bool {
+        digits
+            .width()
+            .is_none_or(|width| digits.format(id).len() == width as usize)
+    }
+    const DECIMAL: Digits = Digits::DECIMAL;
+
     const INPUT: &str = r#"
 11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124
 "#;
     #[test]
     fn part1() -> Result<()> {
-        let ranges = parse(INPUT.trim())?;
-        assert_eq!(invalid_ids_sum(&ranges, is_invalid_id_part1), 1227775554);
+        let ranges = parse(INPUT.trim(), DECIMAL)?;
+        assert_eq!(
+            invalid_ids_sum(&ranges, &PART1, DECIMAL, false).sum,
+            1227775554
+        );
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        let ranges = parse(INPUT.trim())?;
-        assert_eq!(invalid_ids_sum(&ranges, is_invalid_id_part2), 4174379265);
+        let ranges = parse(INPUT.trim(), DECIMAL)?;
+        assert_eq!(
+            invalid_ids_sum(&ranges, &PART2, DECIMAL, false).sum,
+            4174379265
+        );
+        Ok(())
+    }
+    #[test]
+    fn counts_and_lists_policy_matches() -> Result<()> {
+        let ranges = parse(INPUT.trim(), DECIMAL)?;
+        let matches = invalid_ids_sum(&ranges, &PART1, DECIMAL, true);
+        assert_eq!(matches.count, 8);
+        assert_eq!(
+            matches.ids,
+            Some(vec![11, 22, 99, 1010, 1188511885, 222222, 446446, 38593859])
+        );
+        let thrice = invalid_ids_sum(&ranges, &Policy::Exactly(3), DECIMAL, false);
+        assert_eq!(
+            (thrice.count, thrice.sum, thrice.ids),
+            (5, 111 + 999 + 222222 + 565656 + 824824824, None)
+        );
+        Ok(())
+    }
+    #[test]
+    fn matches_string_predicates() -> Result<()> {
+        let hex = "0-1000,fff0-10010,abab0-abacf";
+        for (input, digits) in [
+            (INPUT.trim(), DECIMAL),
+            (hex, Digits::new(16, None)?),
+            ("0-1000,ff00-ffff", Digits::new(16, Some(4))?),
+            ("0-zz,zzz0-1000z", Digits::new(36, None)?),
+        ] {
+            let ranges = parse(input, digits)?;
+            let brute_force = |pred: fn(u64, Digits) -> bool| {
+                ranges
+                    .iter()
+                    .flat_map(|range| range.clone())
+                    .filter(|id| fits(*id, digits) && pred(*id, digits))
+                    .map(u128::from)
+                    .sum::<u128>()
+            };
+            assert_eq!(
+                invalid_ids_sum(&ranges, &PART1, digits, false).sum,
+                brute_force(is_invalid_id_part1)
+            );
+            assert_eq!(
+                invalid_ids_sum(&ranges, &PART2, digits, false).sum,
+                brute_force(is_invalid_id_part2)
+            );
+        }
+        assert!(parse(hex, Digits::new(16, Some(4))?).is_err());
         Ok(())
     }
 }
@@ -1,12 +1,16 @@
 use anyhow::{Ok, Result};
+use aoc_2025::numeric::{CheckedArith, Num, try_sum};
+use aoc_2025::parsers::{self, grid};
+use aoc_2025::{trace, traced};
 use itertools::Itertools;
+use num::Zero;
 use std::fs::read_to_string;
 
 #[derive(Debug, Clone)]
 struct Bank(Vec<i64>);
 
 impl Bank {
-    fn max_jolt2(&self) -> i64 {
+    fn max_jolt2(&self) -> Num {
         let bank_iter = self.0.iter().map(|&j| -j);
         let max_pos1 = bank_iter
             .clone()
@@ -25,13 +29,14 @@ impl Bank {
                 + max_pos1
                 + 1
         };
-        if max_pos1 > max_pos2 {
+        let jolt = if max_pos1 > max_pos2 {
             self.0[max_pos2] * 10 + self.0[max_pos1]
         } else {
             self.0[max_pos1] * 10 + self.0[max_pos2]
-        }
+        };
+        Num::from(jolt as u64)
     }
-    fn max_jolt12(&self) -> i64 {
+    fn max_jolt12(&self) -> Result<Num> {
         const MAX_BATTARIES: usize = 12;
         let mut max_jolt_idxs = Vec::with_capacity(MAX_BATTARIES);
         let mut cur_slice = &self.0[..];
@@ -45,16 +50,20 @@ impl Bank {
                 cur_slice = &self.0[next_start_pos + max_pos + 1..];
             }
         }
+        let ten = Num::from(10_u32);
         max_jolt_idxs
             .iter()
             .sorted()
-            .rev()
-            .enumerate()
-            .fold(0, |acc, (i, jolt)| {
-                acc + self.0[*jolt] * 10_i64.pow(i as u32)
+            .try_fold(Num::zero(), |acc, jolt| {
+                acc.try_mul(&ten)?.try_add(&Num::from(self.0[*jolt] as u64))
             })
     }
 }
+fn total_max_jolt12(banks: &[Bank]) -> Result<Num> {
+    banks
+        .iter()
+        .try_fold(Num::zero(), |acc, bank| acc.try_add(&bank.max_jolt12()?))
+}
 fn max_in_slice(slice: &[i64]) -> usize {
     slice
         .iter()
@@ -63,24 +72,18 @@ fn max_in_slice(slice: &[i64]) -> usize {
         .expect("empty slice")
 }
 
-fn parse(input: &str) -> Vec<Bank> {
-    input
-        .lines()
-        .map(|line| {
-            Bank(
-                line.chars()
-                    .map(|c| c.to_digit(10).expect("digit") as i64)
-                    .collect(),
-            )
-        })
-        .collect()
+fn parse(input: &str) -> Result<Vec<Bank>> {
+    let digit = |c: char| c.to_digit(10).map(i64::from);
+    let banks = parsers::run(grid(digit), input)?;
+    Ok(banks.into_iter().map(Bank).collect())
 }
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day03-input1.txt")?;
-    let banks = parse(input.trim());
-    let answer = banks.iter().map(|bank| bank.max_jolt2()).sum::<i64>();
+    let banks = traced!("parse", parse(input.trim()))?;
+    let answer = traced!("part1", try_sum(banks.iter().map(|bank| bank.max_jolt2())))?;
     println!("part 1 answer is: {answer}");
-    let answer = banks.iter().map(|bank| bank.max_jolt12()).sum::<i64>();
+    let answer = traced!("part2", total_max_jolt12(&banks))?;
     println!("part 2 answer is: {answer}");
     Ok(())
 }
@@ -97,23 +100,14 @@ mod tests {
 "#;
     #[test]
     fn part1() -> Result<()> {
-        let answer = parse(INPUT.trim())
-            .iter()
-            .map(|bank| bank.max_jolt2())
-            .sum::<i64>();
-        assert_eq!(answer, 357);
+        let answer = try_sum(parse(INPUT.trim())?.iter().map(|bank| bank.max_jolt2()))?;
+        assert_eq!(answer, Num::from(357_u32));
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        let answer = parse(INPUT.trim())
-            .iter()
-            .map(|bank| bank.max_jolt12())
-            .inspect(|j| {
-                eprintln!("j = {:#?}", j);
-            })
-            .sum::<i64>();
-        assert_eq!(answer, 3121910778619);
+        let answer = total_max_jolt12(&parse(INPUT.trim())?)?;
+        assert_eq!(answer, Num::from(3121910778619_u64));
         Ok(())
     }
 }
@@ -1,61 +1,88 @@
 use anyhow::{Ok, Result};
-use glam::IVec2;
-use std::collections::HashSet;
+use aoc_2025::animate::{Animation, Canvas};
+use aoc_2025::automaton::{Erosion, MOORE};
+use aoc_2025::parsers::{self, grid};
+use aoc_2025::render::{GREY, HIGHLIGHT, Scene, heat, render_target};
+use aoc_2025::{trace, traced};
 use std::fs::read_to_string;
+use std::path::Path;
 
 #[derive(Debug, Clone)]
-struct Map(HashSet<IVec2>);
+struct Map(Vec<Vec<bool>>);
 
-fn parse(input: &str) -> Map {
-    let map = input
-        .lines()
-        .enumerate()
-        .flat_map(|(row, line)| {
-            line.chars()
-                .enumerate()
-                .filter(|(_col, ch)| *ch == '@')
-                .map(move |(col, _ch)| IVec2::new(row as i32, col as i32))
-        })
-        .collect();
-
-    Map(map)
+fn parse(input: &str) -> Result<Map> {
+    let cells = parsers::run(grid(|c| matches!(c, '@' | '.').then_some(c == '@')), input)?;
+    Ok(Map(cells))
 }
-fn rolls_can_be_accessed(map: &Map) -> impl Iterator<Item = IVec2> {
-    map.0.iter().filter_map(|roll| {
-        let adj_rolls = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ]
-        .iter()
-        .filter(|&adj| map.0.contains(&(roll + IVec2::from(*adj))))
-        .count();
-        if adj_rolls < 4 { Some(*roll) } else { None }
-    })
+/// A roll can be reached by a forklift with fewer than four rolls around it.
+fn forklift_erosion(map: &Map) -> Erosion<impl Fn(usize) -> bool> {
+    Erosion::from_grid(&map.0, &MOORE, |adj_rolls| adj_rolls < 4)
+}
+fn rolls_can_be_accessed(map: &Map) -> usize {
+    forklift_erosion(map).next().map_or(0, |wave| wave.len())
 }
-fn rolls_can_be_removed(map: &mut Map) -> usize {
-    let rolls_to_remove = rolls_can_be_accessed(map).collect::<Vec<_>>();
-    if rolls_to_remove.is_empty() {
-        return 0;
+fn rolls_can_be_removed(map: &Map) -> usize {
+    forklift_erosion(map).map(|wave| wave.len()).sum()
+}
+/// Rolls coloured by the wave that removes them, rolls that stay are grey.
+fn render(map: &Map, path: &Path) -> Result<()> {
+    let height = map.0.len();
+    let width = map.0.first().map_or(0, Vec::len);
+    let mut scene = Scene::new(width as i32, height as i32);
+    let mut erosion = forklift_erosion(map);
+    let waves: Vec<_> = erosion.by_ref().collect();
+    for (i, wave) in waves.iter().enumerate() {
+        let colour = heat(i as f64 / waves.len().saturating_sub(1).max(1) as f64);
+        wave.iter().for_each(|roll| scene.cell(*roll, colour));
     }
-    for roll in &rolls_to_remove {
-        map.0.remove(roll);
+    erosion
+        .live()
+        .iter()
+        .for_each(|roll| scene.cell(*roll, GREY));
+    scene.save(path)
+}
+/// Each wave highlighted on the rolls that are still standing.
+fn animate(map: &Map, animation: &mut Animation) -> Result<()> {
+    let height = map.0.len();
+    let width = map.0.first().map_or(0, Vec::len);
+    let mut erosion = forklift_erosion(map);
+    let mut removed = 0;
+    for wave in 1.. {
+        let doomed = erosion.doomed();
+        let title = format!("wave {wave}: removing {}, removed {removed}", doomed.len());
+        animation.frame(&title, || {
+            let mut canvas = Canvas::new(width, height);
+            erosion
+                .live()
+                .iter()
+                .for_each(|roll| canvas.set(*roll, '@', GREY));
+            doomed
+                .iter()
+                .for_each(|roll| canvas.set(*roll, '@', HIGHLIGHT));
+            canvas
+        })?;
+        match erosion.step() {
+            Some(wave) => removed += wave.len(),
+            None => break,
+        }
     }
-    rolls_to_remove.len() + rolls_can_be_removed(map)
+    Ok(())
 }
 
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day04-input1.txt")?;
-    let mut map = parse(input.trim());
-    let answer = rolls_can_be_accessed(&map).count();
+    let map = traced!("parse", parse(input.trim()))?;
+    let answer = traced!("part1", rolls_can_be_accessed(&map));
     println!("part 1 answer is: {answer}");
-    let answer = rolls_can_be_removed(&mut map);
+    let answer = traced!("part2", rolls_can_be_removed(&map));
     println!("part 2 answer is: {answer}");
+    if let Some(path) = render_target()? {
+        render(&map, &path)?;
+    }
+    if let Some(mut animation) = Animation::from_args()? {
+        animate(&map, &mut animation)?;
+    }
     Ok(())
 }
 
@@ -77,15 +104,15 @@ mod tests {
 "#;
     #[test]
     fn part1() -> Result<()> {
-        let map = parse(INPUT.trim());
-        let answer = rolls_can_be_accessed(&map).count();
+        let map = parse(INPUT.trim())?;
+        let answer = rolls_can_be_accessed(&map);
         assert_eq!(answer, 13);
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        let mut map = parse(INPUT.trim());
-        let answer = rolls_can_be_removed(&mut map);
+        let map = parse(INPUT.trim())?;
+        let answer = rolls_can_be_removed(&map);
         assert_eq!(answer, 43);
         Ok(())
     }
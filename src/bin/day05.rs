@@ -1,4 +1,7 @@
 use anyhow::{Ok, Result};
+use aoc_2025::parsers::{self, blank_line, lines, range, unsigned};
+use aoc_2025::{trace, traced};
+use nom::sequence::separated_pair;
 use std::cmp::Ordering;
 use std::fs::read_to_string;
 use std::ops::RangeInclusive;
@@ -9,24 +12,15 @@ struct Cafeteria {
     available_ids: Vec<u64>,
 }
 
-fn parse(input: &str) -> Cafeteria {
-    let fresh_ranges: Vec<RangeInclusive<u64>> = input
-        .lines()
-        .flat_map(|line| {
-            line.split_once('-').map(|(start, end)| {
-                start.parse().expect("parse start failed")..=end.parse().expect("parse end failed")
-            })
-        })
-        .collect();
-    let available_ids = input
-        .lines()
-        .skip(fresh_ranges.len() + 1)
-        .map(|id| id.parse().expect("parse id"))
-        .collect();
-    Cafeteria {
+fn parse(input: &str) -> Result<Cafeteria> {
+    let (fresh_ranges, available_ids) = parsers::run(
+        separated_pair(lines(range), blank_line, lines(unsigned)),
+        input,
+    )?;
+    Ok(Cafeteria {
         fresh_ranges,
         available_ids,
-    }
+    })
 }
 fn fresh_from_available_ids(cafe: &Cafeteria) -> usize {
     cafe.available_ids
@@ -58,11 +52,12 @@ fn fresh_from_ranges(cafe: &mut Cafeteria) -> u64 {
 }
 
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day05-input1.txt")?;
-    let mut cafe = parse(input.trim());
-    let answer = fresh_from_available_ids(&cafe);
+    let mut cafe = traced!("parse", parse(input.trim()))?;
+    let answer = traced!("part1", fresh_from_available_ids(&cafe));
     println!("part 1 answer is: {answer}");
-    let answer2 = fresh_from_ranges(&mut cafe);
+    let answer2 = traced!("part2", fresh_from_ranges(&mut cafe));
     println!("part 2 answer is: {answer2}");
     Ok(())
 }
@@ -85,14 +80,14 @@ mod tests {
 "#;
     #[test]
     fn part1() -> Result<()> {
-        let cafe = parse(INPUT.trim());
+        let cafe = parse(INPUT.trim())?;
         let answer = fresh_from_available_ids(&cafe);
         assert_eq!(answer, 3);
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        let mut cafe = parse(INPUT.trim());
+        let mut cafe = parse(INPUT.trim())?;
         let answer = fresh_from_ranges(&mut cafe);
         assert_eq!(answer, 14);
         Ok(())
@@ -1,4 +1,12 @@
-use anyhow::{Ok, Result};
+use anyhow::{Ok, Result, anyhow};
+use aoc_2025::layout::Layout;
+use aoc_2025::numeric::{CheckedArith, Num, try_product, try_sum};
+use aoc_2025::parsers::{self, IResult, padded, unsigned};
+use aoc_2025::{trace, traced};
+use nom::Parser;
+use nom::branch::alt;
+use nom::character::complete::char;
+use num::Zero;
 use std::fs::read_to_string;
 
 #[derive(Debug, Clone, Copy)]
@@ -6,101 +14,81 @@ enum Op {
     Plus,
     Mul,
 }
+/// One problem per column group of the worksheet, its numbers read either
+/// row-wise (part 1) or column-wise right to left (part 2).
 #[derive(Debug, Clone)]
-struct Homework {
-    numbers: Vec<Vec<u64>>,
-    ops: Vec<Op>,
+struct Problem {
+    op: Op,
+    row_numbers: Vec<u64>,
+    column_numbers: Vec<u64>,
 }
 
-impl Homework {
-    fn solve(&self) -> u64 {
-        let mut result = 0;
-        for i in 0..self.ops.len() {
-            result += match self.ops[i] {
-                Op::Plus => self.numbers.iter().fold(0, |acc, col| acc + col[i]),
-                Op::Mul => self.numbers.iter().fold(1, |acc, col| acc * col[i]),
-            }
+impl Problem {
+    fn eval(&self, numbers: &[u64]) -> Result<Num> {
+        let numbers = numbers.iter().copied().map(Num::from);
+        match self.op {
+            Op::Plus => try_sum(numbers),
+            Op::Mul => try_product(numbers),
         }
-        result
     }
 }
-fn parse_col(lines: &[&[u8]], i: usize) -> u64 {
-    let mut num_as_str = String::new();
-    for line in lines {
-        if line[i] == b' ' {
-            continue;
-        }
-        num_as_str.push(line[i].into());
+
+#[derive(Debug, Clone)]
+struct Homework(Vec<Problem>);
+
+impl Homework {
+    fn total(&self, numbers: fn(&Problem) -> &[u64]) -> Result<Num> {
+        self.0
+            .iter()
+            .try_fold(Num::zero(), |acc, p| acc.try_add(&p.eval(numbers(p))?))
+    }
+    fn solve(&self) -> Result<Num> {
+        self.total(|p| &p.row_numbers)
+    }
+    fn solve2(&self) -> Result<Num> {
+        self.total(|p| &p.column_numbers)
     }
-    num_as_str.parse::<u64>().unwrap()
 }
 
-fn solve2(input: &str) -> u64 {
-    let len = input.lines().count();
-    let ops = input
-        .lines()
-        .nth(len - 1)
-        .unwrap()
-        .match_indices(['*', '+'])
-        .map(|(i, c)| {
-            (
-                i,
-                match c {
-                    "*" => Op::Mul,
-                    "+" => Op::Plus,
-                    _ => unreachable!("no other operations"),
-                },
-            )
-        })
-        .collect::<Vec<_>>();
-    let lines: Vec<_> = input.lines().take(len - 1).map(|s| s.as_bytes()).collect();
-    let mut col_end = lines[0].len();
-    let mut result = 0;
-    for (col_start, op) in ops.iter().rev() {
-        result += match op {
-            Op::Plus => (*col_start..col_end)
-                .rev()
-                .fold(0, |acc, i| acc + parse_col(&lines, i)),
-            Op::Mul => (*col_start..col_end)
-                .rev()
-                .fold(1, |acc, i| acc * parse_col(&lines, i)),
-        };
-        col_end = col_start.saturating_sub(1);
-    }
-    result
+fn op(input: &str) -> IResult<'_, Op> {
+    alt((char('+').map(|_| Op::Plus), char('*').map(|_| Op::Mul))).parse(input)
 }
-fn parse(input: &str) -> Homework {
-    let numbers: Vec<_> = input
-        .lines()
-        .filter(|line| line.starts_with(|c: char| c != '*' && c != '+'))
-        .map(|line| {
-            line.split_whitespace()
-                .map(|num_as_str| num_as_str.parse().unwrap())
-                .collect()
-        })
-        .collect();
-    let ops = input
-        .lines()
-        .skip(numbers.len())
-        .map(|line| {
-            line.split_whitespace()
-                .map(|c| match c {
-                    "*" => Op::Mul,
-                    "+" => Op::Plus,
-                    _ => unreachable!("no other operations"),
-                })
-                .collect()
+/// The last line holds the operators, left aligned under their problem.
+fn parse(input: &str) -> Result<Homework> {
+    let layout = Layout::new(input);
+    let number_rows = 0..layout.height().saturating_sub(1);
+    let problems = layout
+        .groups()
+        .map(|group| {
+            let rows = group.rows();
+            let (op_row, rows) = rows.split_last().ok_or(anyhow!("empty worksheet"))?;
+            let row_numbers = rows
+                .iter()
+                .map(|row| parsers::run(padded(unsigned), row))
+                .collect::<Result<_>>()?;
+            let column_numbers = group
+                .columns_in(number_rows.clone())
+                .iter()
+                .rev()
+                .map(|column| parsers::run(padded(unsigned), column))
+                .collect::<Result<_>>()?;
+            Ok(Problem {
+                op: parsers::run(padded(op), op_row)?,
+                row_numbers,
+                column_numbers,
+            })
         })
-        .next()
-        .unwrap();
-    Homework { numbers, ops }
+        .collect::<Result<_>>()?;
+    Ok(Homework(problems))
 }
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day06-input1.txt")?;
-    let homework = parse(input.trim());
-    let answer = homework.solve();
+    // leading spaces of the first line are part of the alignment
+    let homework = traced!("parse", parse(input.trim_matches('\n')))?;
+    let answer = traced!("part1", homework.solve())?;
     println!("part 1 answer is: {answer}");
-    let answer = solve2(input.trim());
+    let answer = traced!("part2", homework.solve2())?;
     println!("part 2 answer is: {answer}");
     Ok(())
 }
@@ -117,15 +105,16 @@ mod tests {
 "#;
     #[test]
     fn part1() -> Result<()> {
-        let homework = parse(INPUT.trim());
-        let answer = homework.solve();
-        assert_eq!(answer, 4277556);
+        let homework = parse(INPUT.trim_matches('\n'))?;
+        let answer = homework.solve()?;
+        assert_eq!(answer, Num::from(4277556_u32));
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        let answer = solve2(INPUT.trim());
-        assert_eq!(answer, 3263827);
+        let homework = parse(INPUT.trim_matches('\n'))?;
+        let answer = homework.solve2()?;
+        assert_eq!(answer, Num::from(3263827_u32));
         Ok(())
     }
 }
@@ -1,89 +1,158 @@
-use anyhow::{Ok, Result};
+use anyhow::{Ok, Result, anyhow};
+use aoc_2025::animate::{Animation, Canvas};
+use aoc_2025::numeric::{CheckedArith, Num};
+use aoc_2025::parsers::{self, grid};
+use aoc_2025::render::{GREY, HIGHLIGHT, Scene, heat, render_target};
+use aoc_2025::{trace, traced};
 use glam::IVec2;
+use itertools::Itertools;
+use num::{One, ToPrimitive, Zero};
 use std::collections::{HashMap, HashSet};
 use std::fs::read_to_string;
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 struct Map {
     start: IVec2,
     splitters: HashSet<IVec2>,
-    beams: HashMap<IVec2, usize>,
+    beams: HashMap<IVec2, Num>,
+    width: usize,
     height: usize,
     splits: usize,
 }
 
 impl Map {
-    fn beam_step_down(&mut self, beam: IVec2) {
-        let timeline = *self.beams.get(&beam).expect("beam not found");
+    fn add_timelines(&mut self, beam: IVec2, timeline: &Num) -> Result<()> {
+        let tl = self.beams.entry(beam).or_default();
+        *tl = tl.try_add(timeline)?;
+        Ok(())
+    }
+    fn beam_step_down(&mut self, beam: IVec2) -> Result<()> {
+        // beams of finished rows are dropped anyway
+        let timeline = self.beams.remove(&beam).expect("beam not found");
         if self.splitters.contains(&(beam + IVec2::new(0, 1))) {
-            self.beams
-                .entry(beam + IVec2::new(-1, 1))
-                .and_modify(|tl| *tl += timeline)
-                .or_insert(timeline);
-            self.beams
-                .entry(beam + IVec2::new(1, 1))
-                .and_modify(|tl| *tl += timeline)
-                .or_insert(timeline);
+            self.add_timelines(beam + IVec2::new(-1, 1), &timeline)?;
+            self.add_timelines(beam + IVec2::new(1, 1), &timeline)?;
             self.splits += 1;
         } else {
-            self.beams
-                .entry(beam + IVec2::new(0, 1))
-                .and_modify(|tl| *tl += timeline)
-                .or_insert(timeline);
+            self.add_timelines(beam + IVec2::new(0, 1), &timeline)?;
         }
+        Ok(())
     }
-    fn run_beams(&mut self) {
-        self.beams.insert(self.start, 1);
-        self.beam_step_down(self.start);
+    fn run_beams(&mut self) -> Result<()> {
+        self.run_beams_with(|_| Ok(()))
+    }
+    /// Runs the beams, `on_row` sees the beams of every row below the start.
+    fn run_beams_with(
+        &mut self,
+        mut on_row: impl FnMut(&HashMap<IVec2, Num>) -> Result<()>,
+    ) -> Result<()> {
+        self.beams.insert(self.start, Num::one());
+        self.beam_step_down(self.start)?;
+        on_row(&self.beams)?;
         for row in 1..self.height {
             self.beams.retain(|beam,_| beam.y == row as i32);
             let beams: Vec<_> = self.beams.keys().cloned().collect();
-            beams.into_iter().for_each(|beam| self.beam_step_down(beam));
+            for beam in beams {
+                self.beam_step_down(beam)?;
+            }
+            on_row(&self.beams)?;
         }
+        Ok(())
     }
-    fn beam_timelines(&self) -> usize {
+    fn beam_timelines(&self) -> Result<Num> {
         self.beams
             .iter()
             .filter(|(beam, _)| beam.y == self.height as i32)
-            .map(|(_, tl)| tl)
-            .sum()
+            .try_fold(Num::zero(), |acc, (_, tl)| acc.try_add(tl))
     }
 }
 
-fn parse(input: &str) -> Map {
-    let start = IVec2 {
-        x: input.find('S').unwrap() as i32,
-        y: 0,
-    };
-    let height = input.lines().count();
-    let splitters = input
-        .lines()
-        .enumerate()
-        .flat_map(|(row, line)| {
-            line.chars()
+fn parse(input: &str) -> Result<Map> {
+    let cell = |c| matches!(c, '.' | 'S' | '^').then_some(c);
+    let cells = parsers::run(grid(cell), input)?;
+    let positions = |wanted: char| {
+        cells.iter().enumerate().flat_map(move |(row, line)| {
+            line.iter()
                 .enumerate()
-                .filter(|(_col, ch)| *ch == '^')
+                .filter(move |(_col, ch)| **ch == wanted)
                 .map(move |(col, _ch)| IVec2::new(col as i32, row as i32))
         })
-        .collect();
-
-    Map {
+    };
+    let start = positions('S')
+        .exactly_one()
+        .map_err(|_| anyhow!("expected exactly one start `S`"))?;
+    Ok(Map {
         start,
-        splitters,
+        splitters: positions('^').collect(),
         beams: HashMap::new(),
-        height,
+        width: cells.first().map_or(0, Vec::len),
+        height: cells.len(),
         splits: 0,
+    })
+}
+/// Beam paths brighter the more timelines run through them, on a log scale.
+fn render(mut map: Map, path: &Path) -> Result<()> {
+    let mut trail = vec![];
+    map.run_beams_with(|beams| {
+        for (beam, tl) in beams {
+            trail.push((*beam, tl.to_f64().unwrap_or(f64::MAX)));
+        }
+        Ok(())
+    })?;
+    let brightest = trail.iter().map(|(_, tl)| tl.ln_1p()).fold(1.0, f64::max);
+    let mut scene = Scene::new(map.width as i32, map.height as i32);
+    for (beam, tl) in trail {
+        scene.cell(beam, heat(tl.ln_1p() / brightest));
     }
+    for splitter in &map.splitters {
+        scene.cell(*splitter, GREY);
+    }
+    scene.cell(map.start, HIGHLIGHT);
+    scene.save(path)
+}
+/// Beams advancing one row per frame, brighter with more timelines.
+fn animate(mut map: Map, animation: &mut Animation) -> Result<()> {
+    let (start, width, height) = (map.start, map.width, map.height);
+    let splitters = map.splitters.clone();
+    let mut trail: HashMap<IVec2, f64> = HashMap::new();
+    let mut row = start.y;
+    map.run_beams_with(|beams| {
+        row += 1;
+        for (beam, tl) in beams {
+            trail.insert(*beam, tl.to_f64().unwrap_or(f64::MAX).ln_1p());
+        }
+        let brightest = trail.values().copied().fold(1.0, f64::max);
+        let title = format!("row {row}/{height}: {} beams", beams.len());
+        animation.frame(&title, || {
+            let mut canvas = Canvas::new(width, height);
+            for (beam, tl) in &trail {
+                canvas.set(*beam, '|', heat(tl / brightest));
+            }
+            for splitter in &splitters {
+                canvas.set(*splitter, '^', GREY);
+            }
+            canvas.set(start, 'S', HIGHLIGHT);
+            canvas
+        })
+    })
 }
 
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day07-input1.txt")?;
-    let mut map = parse(input.trim());
-    map.run_beams();
+    let mut map = traced!("parse", parse(input.trim()))?;
+    traced!("part1", map.run_beams())?;
     let answer = map.splits;
     println!("part 1 answer is: {answer}");
-    let answer = map.beam_timelines();
+    let answer = traced!("part2", map.beam_timelines())?;
     println!("part 2 answer is: {answer}");
+    if let Some(path) = render_target()? {
+        render(parse(input.trim())?, &path)?;
+    }
+    if let Some(mut animation) = Animation::from_args()? {
+        animate(parse(input.trim())?, &mut animation)?;
+    }
     Ok(())
 }
 
@@ -112,18 +181,18 @@ mod tests {
 
     #[test]
     fn part1() -> Result<()> {
-        let mut map = parse(INPUT.trim());
-        map.run_beams();
+        let mut map = parse(INPUT.trim())?;
+        map.run_beams()?;
         let answer = map.splits;
         assert_eq!(answer, 21);
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        let mut map = parse(INPUT.trim());
-        map.run_beams();
-        let answer = map.beam_timelines();
-        assert_eq!(answer, 40);
+        let mut map = parse(INPUT.trim())?;
+        map.run_beams()?;
+        let answer = map.beam_timelines()?;
+        assert_eq!(answer, Num::from(40_u32));
         Ok(())
     }
 }
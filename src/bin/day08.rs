@@ -1,4 +1,7 @@
 use anyhow::{Ok, Result};
+use aoc_2025::kdtree::KdTree;
+use aoc_2025::parsers::{self, i64vec3, lines};
+use aoc_2025::{trace, trace_event, traced};
 use glam::I64Vec3;
 use itertools::Itertools;
 use std::collections::HashSet;
@@ -20,19 +23,8 @@ impl JBoxes {
         }
     }
 
-// TODO: make it as iterator
     fn min_distances(&self) -> impl Iterator<Item = [usize; 2]> + use<> {
-        let mut result = vec![];
-        for i in 0..self.jboxes.len() {
-            for j in i + 1..self.jboxes.len() {
-                let dist = self.jboxes[i].distance_squared(self.jboxes[j]);
-                result.push(([i, j], dist));
-            }
-        }
-        result
-            .into_iter()
-            .sorted_by_key(|(_, dist)| *dist)
-            .map(|(jbox_ids, _)| jbox_ids)
+        KdTree::new(self.jboxes.clone()).closest_pairs()
     }
     fn construct_circuits(&mut self, min_dists_iter: impl Iterator<Item = [usize; 2]>) {
         for conn in min_dists_iter {
@@ -53,6 +45,12 @@ impl JBoxes {
             } else {
                 // join existing circuits
                 let removed = self.circuits.remove(intersect_circuit_ids[1]);
+                trace_event!(
+                    "circuits merged",
+                    into = self.circuits[intersect_circuit_ids[0]].len(),
+                    absorbed = removed.len(),
+                    circuits = self.circuits.len(),
+                );
                 self.circuits[intersect_circuit_ids[0]].extend(removed);
             }
             self.last_conn = [self.jboxes[conn[0]], self.jboxes[conn[1]]];
@@ -64,36 +62,30 @@ impl JBoxes {
     }
 }
 
-fn parse(input: &str) -> JBoxes {
-    let jboxes = input
-        .lines()
-        .map(|line| {
-            let arr = line
-                .splitn(3, ',')
-                .map(|coord| coord.parse::<i64>().unwrap())
-                .collect_array()
-                .expect("parse line failed");
-            I64Vec3::from_array(arr)
-        })
-        .collect();
-    JBoxes::new(jboxes)
+fn parse(input: &str) -> Result<JBoxes> {
+    Ok(JBoxes::new(parsers::run(lines(i64vec3), input)?))
 }
 
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day08-input1.txt")?;
-    let mut jboxes = parse(input.trim());
-    jboxes.construct_circuits(jboxes.min_distances().take(1000));
-    let answer = jboxes
-        .circuits
-        .iter()
-        .map(|circuit| circuit.len())
-        .sorted_by_key(|&len| -(len as isize))
-        .take(3)
-        .product::<usize>();
+    let mut jboxes = traced!("parse", parse(input.trim()))?;
+    let answer = traced!("part1", {
+        jboxes.construct_circuits(jboxes.min_distances().take(1000));
+        jboxes
+            .circuits
+            .iter()
+            .map(|circuit| circuit.len())
+            .sorted_by_key(|&len| -(len as isize))
+            .take(3)
+            .product::<usize>()
+    });
     println!("part 1 answer is: {answer}");
 
-    jboxes.construct_circuits(jboxes.min_distances().skip(1000));
-    let answer = jboxes.last_conn[0].x * jboxes.last_conn[1].x;
+    let answer = traced!("part2", {
+        jboxes.construct_circuits(jboxes.min_distances().skip(1000));
+        jboxes.last_conn[0].x * jboxes.last_conn[1].x
+    });
     println!("part 2 answer is: {answer}");
     Ok(())
 }
@@ -126,7 +118,7 @@ mod tests {
 "#;
     #[test]
     fn part1() -> Result<()> {
-        let mut jboxes = parse(INPUT.trim());
+        let mut jboxes = parse(INPUT.trim())?;
         jboxes.construct_circuits(jboxes.min_distances().take(10));
         let answer = jboxes
             .circuits
@@ -141,7 +133,7 @@ mod tests {
     #[test]
     fn part2() -> Result<()> {
         // 10 12
-        let mut jboxes = parse(INPUT.trim());
+        let mut jboxes = parse(INPUT.trim())?;
         jboxes.construct_circuits(jboxes.min_distances());
         let answer = jboxes.last_conn[0].x * jboxes.last_conn[1].x;
         assert_eq!(answer, 25272);
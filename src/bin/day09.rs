@@ -1,8 +1,11 @@
 use anyhow::{Ok, Result};
+use aoc_2025::geometry::OrthoPolygon;
+use aoc_2025::parsers::{self, ivec2, lines};
+use aoc_2025::render::{HIGHLIGHT, Scene, palette, render_target};
+use aoc_2025::{trace, traced};
 use glam::IVec2;
-use std::cmp::Ordering;
 use std::fs::read_to_string;
-use std::ops::Range;
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 struct RedTileList {
@@ -17,134 +20,89 @@ impl RedTileList {
         let len = self.coords.len();
         for i in 0..len {
             for j in i + 1..len {
-                let area = ((self.coords[i].x - self.coords[j].x).abs() + 1) as i64
-                    * ((self.coords[i].y - self.coords[j].y).abs() + 1) as i64;
-                max_area = max_area.max(area);
+                max_area = max_area.max(rect_area(self.coords[i], self.coords[j]));
             }
         }
         max_area
     }
-    fn max_area_in_polygon(&self) -> i64 {
-        let mut max_area = 0;
-        let len = self.coords.len();
-        for i in 0..len {
-            for j in i + 1..len {
-                let area = ((self.coords[i].x - self.coords[j].x).abs() + 1) as i64
-                    * ((self.coords[i].y - self.coords[j].y).abs() + 1) as i64;
-                if max_area > area {
-                    continue;
-                }
-                if !self.is_smaller_rect_in_polygon(i, j) {
-                    continue;
-                }
-                max_area = area;
-            }
-        }
-        max_area
-    }
-    fn is_intersects_edge(&self, edge: [IVec2; 2]) -> bool {
-        self.coords
-            .iter()
-            .zip(self.coords.iter().skip(1))
-            .map(|(a, b)| [*a, *b])
-            .any(|poly_edge| {
-                let (ax, ay) = ranges_from_edge(edge);
-                let (bx, by) = ranges_from_edge(poly_edge);
-                ax.contains(&bx.start) && by.contains(&ay.start)
-                    || ay.contains(&by.start) && bx.contains(&ax.start)
-            })
+    fn build_polygon(&self) -> Result<OrthoPolygon> {
+        OrthoPolygon::from_unordered(&self.coords)
     }
-    fn is_smaller_rect_in_polygon(&self, i: usize, j: usize) -> bool {
-        let sign_x = (self.coords[i].x - self.coords[j].x).signum();
-        let sign_y = (self.coords[i].y - self.coords[j].y).signum();
-        let edge_ix = [
-            IVec2::new(self.coords[i].x - sign_x, self.coords[i].y - sign_y),
-            IVec2::new(self.coords[j].x + sign_x, self.coords[i].y - sign_y),
-        ];
-        let edge_iy = [
-            IVec2::new(self.coords[i].x - sign_x, self.coords[i].y - sign_y),
-            IVec2::new(self.coords[i].x - sign_x, self.coords[j].y + sign_y),
-        ];
-        let edge_jx = [
-            IVec2::new(self.coords[j].x + sign_x, self.coords[j].y + sign_y),
-            IVec2::new(self.coords[i].x - sign_x, self.coords[j].y + sign_y),
-        ];
-        let edge_jy = [
-            IVec2::new(self.coords[j].x + sign_x, self.coords[j].y + sign_y),
-            IVec2::new(self.coords[j].x + sign_x, self.coords[i].y - sign_y),
-        ];
-        [edge_ix, edge_iy, edge_jx, edge_jy]
-            .iter()
-            .all(|edge| !self.is_intersects_edge(*edge))
-    }
-    fn find_next_red_tile(&self, cur: IVec2) -> Option<IVec2> {
-        self.coords
-            .iter()
-            .copied()
-            .filter(|next| *next != cur)
-            .find(|next| cur.x == next.x || cur.y == next.y)
-    }
-    fn build_polygon(&mut self) {
-        let len = self.coords.len();
-        let mut polygon = Vec::with_capacity(len);
-        let upper_left = self
-            .coords
-            .iter()
-            .copied()
-            .min_by(|a, b| match a.x.cmp(&b.x) {
-                Ordering::Less => Ordering::Less,
-                Ordering::Greater => Ordering::Greater,
-                Ordering::Equal => a.y.cmp(&b.y),
-            })
-            .expect("empty list");
-        polygon.push(
-            self.coords
-                .remove(self.coords.iter().position(|c| *c == upper_left).unwrap()),
-        );
-        let mut cur = upper_left;
-        while let Some(next) = self.find_next_red_tile(cur) {
-            let remove_i = self.coords.iter().position(|c| *c == next).unwrap();
-            polygon.push(self.coords.remove(remove_i));
-            cur = next;
+}
+
+fn rect_area(a: IVec2, b: IVec2) -> i64 {
+    ((a.x - b.x).abs() + 1) as i64 * ((a.y - b.y).abs() + 1) as i64
+}
+
+fn max_area_in_polygon(polygon: &OrthoPolygon) -> i64 {
+    best_rect_in_polygon(polygon).map_or(0, |(area, _, _)| area)
+}
+
+/// Largest rectangle with red corners inside the polygon, with its corners.
+fn best_rect_in_polygon(polygon: &OrthoPolygon) -> Option<(i64, IVec2, IVec2)> {
+    let grid = polygon.compressed_grid();
+    let coords = polygon.vertices();
+    let cells: Vec<_> = coords
+        .iter()
+        .map(|c| grid.cell_index(*c).expect("vertex outside of grid"))
+        .collect();
+    let mut best = None;
+    let len = coords.len();
+    for i in 0..len {
+        for j in i + 1..len {
+            let area = rect_area(coords[i], coords[j]);
+            if best.is_some_and(|(max_area, _, _)| max_area > area) {
+                continue;
+            }
+            if !grid.is_filled(cells[i], cells[j]) {
+                continue;
+            }
+            best = Some((area, coords[i], coords[j]));
         }
-        polygon.push(upper_left);
-        self.coords = polygon;
     }
+    best
 }
 
-fn ranges_from_edge(edge: [IVec2; 2]) -> (Range<i32>, Range<i32>) {
-    let [a, b] = edge;
-    let px = if a.x < b.x { a.x..b.x } else { b.x..a.x };
-    let py = if a.y < b.y { a.y..b.y } else { b.y..a.y };
-    (px, py)
+/// Polygon outline with the largest inscribed rectangle filled in.
+fn render(polygon: &OrthoPolygon, path: &Path) -> Result<()> {
+    let size = polygon
+        .vertices()
+        .iter()
+        .fold(IVec2::ZERO, |size, v| size.max(*v + 1));
+    let mut scene = Scene::new(size.x, size.y);
+    if let Some((_, a, b)) = best_rect_in_polygon(polygon) {
+        scene.rect(a.min(b), (a - b).abs() + 1, palette(1));
+    }
+    scene.outline(polygon.vertices().iter().copied(), HIGHLIGHT);
+    scene.save(path)
 }
 
-fn parse(input: &str) -> RedTileList {
-    let coords = input
-        .lines()
-        .map(|line| {
-            line.split_once(',')
-                .map(|(x, y)| IVec2::from((x.parse().unwrap(), y.parse().unwrap())))
-                .unwrap()
-        })
-        .collect();
-    RedTileList::new(coords)
+fn parse(input: &str) -> Result<RedTileList> {
+    Ok(RedTileList::new(parsers::run(lines(ivec2), input)?))
 }
 
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day09-input1.txt")?;
-    let mut red_tiles = parse(input.trim());
-    let answer = red_tiles.max_area();
+    let red_tiles = traced!("parse", parse(input.trim()))?;
+    let answer = traced!("part1", red_tiles.max_area());
     println!("part 1 answer is: {answer}");
-    red_tiles.build_polygon();
-    let answer = red_tiles.max_area_in_polygon();
+    let (polygon, answer) = traced!("part2", {
+        let polygon = red_tiles.build_polygon()?;
+        let answer = max_area_in_polygon(&polygon);
+        (polygon, answer)
+    });
     println!("part 2 answer is: {answer}");
+    if let Some(path) = render_target()? {
+        render(&polygon, &path)?;
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::Itertools;
 
     const INPUT: &str = r#"
 7,1
@@ -158,17 +116,54 @@ mod tests {
 "#;
     #[test]
     fn part1() -> Result<()> {
-        let red_tiles = parse(INPUT.trim());
+        let red_tiles = parse(INPUT.trim())?;
         let answer = red_tiles.max_area();
         assert_eq!(answer, 50);
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        let mut red_tiles = parse(INPUT.trim());
-        red_tiles.build_polygon();
-        let answer = red_tiles.max_area_in_polygon();
+        let red_tiles = parse(INPUT.trim())?;
+        let polygon = red_tiles.build_polygon()?;
+        let answer = max_area_in_polygon(&polygon);
         assert_eq!(answer, 24);
         Ok(())
     }
+    fn max_area_in_polygon_naive(polygon: &OrthoPolygon) -> i64 {
+        let coords = polygon.vertices();
+        coords
+            .iter()
+            .tuple_combinations()
+            .filter(|(a, b)| polygon.contains_rect(**a, **b))
+            .map(|(a, b)| rect_area(*a, *b))
+            .max()
+            .unwrap_or(0)
+    }
+    #[test]
+    fn part2_matches_naive() -> Result<()> {
+        // staircase around a circle, even coordinates keep lattice and continuous
+        // containment in agreement
+        let ring: Vec<IVec2> = (0..24)
+            .map(|i| {
+                let angle = std::f64::consts::TAU * i as f64 / 24.;
+                IVec2::new(
+                    (200. + 180. * angle.cos()) as i32 * 2,
+                    (200. + 180. * angle.sin()) as i32 * 2,
+                )
+            })
+            .collect();
+        let staircase: Vec<_> = ring
+            .iter()
+            .zip(ring.iter().cycle().skip(1))
+            .flat_map(|(a, b)| [*a, IVec2::new(b.x, a.y)])
+            .collect();
+        for input in [parse(INPUT.trim())?, RedTileList::new(staircase)] {
+            let polygon = input.build_polygon()?;
+            assert_eq!(
+                max_area_in_polygon(&polygon),
+                max_area_in_polygon_naive(&polygon)
+            );
+        }
+        Ok(())
+    }
 }
@@ -1,12 +1,12 @@
-use anyhow::{Ok, Result};
-use glam::bool;
-use lpsolve::Problem;
-use nom::bytes::complete::tag;
-use nom::character::complete::{line_ending, one_of, space1, usize};
-use nom::multi::{many1, separated_list0, separated_list1};
+use anyhow::{Ok, Result, anyhow};
+use aoc_2025::gf2::BitMatrix;
+use aoc_2025::ilp::{DefaultSolver, IlpProblem, IlpSolver};
+use aoc_2025::parsers::{self, bracketed, lines, unsigned};
+use aoc_2025::{trace, trace_event, traced};
+use nom::Parser;
+use nom::character::complete::{char, one_of, space1};
+use nom::multi::{many1, separated_list0};
 use nom::sequence::delimited;
-use nom::{Finish, Parser};
-use std::collections::{HashSet, VecDeque};
 use std::fs::read_to_string;
 
 #[derive(Debug, Clone)]
@@ -16,11 +16,6 @@ impl Button {
     fn new(items: Vec<usize>) -> Self {
         Self(items)
     }
-    fn press(&self, lights_state: &mut [bool]) {
-        for i in self.0.iter().copied() {
-            lights_state[i] = !lights_state[i];
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -28,84 +23,64 @@ struct Machine {
     lights_diagram: Vec<bool>,
     buttons: Vec<Button>,
     joltage_req: Vec<usize>,
-    lights_state: Vec<bool>,
 }
 
 impl Machine {
     fn new(light_diagram: Vec<bool>, buttons: Vec<Button>, joltage_req: Vec<usize>) -> Self {
-        let len = light_diagram.len();
         Self {
             lights_diagram: light_diagram,
             buttons,
             joltage_req,
-            lights_state: vec![false; len],
         }
     }
-    fn lights_reset(&mut self) {
-        self.lights_state
-            .iter_mut()
-            .for_each(|light| *light = false);
-    }
-    fn has_light_state_cycle(&mut self, btn_history: &[usize]) -> bool {
-        let mut light_state = HashSet::new();
-        for b in btn_history.iter() {
-            self.buttons[*b].press(&mut self.lights_state);
-            if !light_state.insert(self.lights_state.clone()) {
-                return true;
+    /// Pressing a button twice cancels out, so each button is pressed at most once
+    /// and the lights are a linear system over GF(2): lights are rows, buttons columns.
+    fn buttons_to_press(&self) -> Result<Vec<usize>> {
+        let mut toggles = BitMatrix::new(self.lights_diagram.len(), self.buttons.len());
+        for (col, btn) in self.buttons.iter().enumerate() {
+            for light in btn.0.iter().copied() {
+                toggles.set(light, col, true);
             }
         }
-        false
+        let presses = toggles
+            .min_weight_solution(&self.lights_diagram)?
+            .ok_or(anyhow!("lights diagram can not be reached"))?;
+        Ok((0..presses.len()).filter(|btn_i| presses[*btn_i]).collect())
     }
-    fn min_buttons_seq(&mut self) -> usize {
-        let mut min = usize::MAX;
-        let mut btn_queue = VecDeque::from_iter((0..self.buttons.len()).map(|btn_i| vec![btn_i]));
-        while let Some(btn_history) = btn_queue.pop_front() {
-            self.lights_reset();
-            if btn_history.len() > min || self.has_light_state_cycle(&btn_history) {
-                continue;
-            } else if self.lights_state == self.lights_diagram {
-                min = btn_history.len().min(min);
-                continue;
-            } else {
-                let last_btn_pressed = btn_history.last().unwrap();
-                for btn_i in (0..self.buttons.len()).filter(|btn_i| btn_i != last_btn_pressed) {
-                    let mut next = btn_history.clone();
-                    next.push(btn_i);
-                    btn_queue.push_back(next);
-                }
-            }
-        }
-        min
+    fn min_buttons_seq(&self) -> Result<usize> {
+        Ok(self.buttons_to_press()?.len())
     }
-    fn min_buttons_joltage(&self) -> usize {
+    fn joltage_problem(&self) -> IlpProblem {
         let cols = self.buttons.len();
-        let mut problem = Problem::builder()
-            .cols(cols as i32)
-            .min(&vec![1.; cols])
-            .integer_vars(&(1..=cols as i32).collect::<Vec<_>>())
-            .non_negative_integers()
-            .verbosity(lpsolve::Verbosity::Critical);
+        let mut problem = IlpProblem::minimize(vec![1.; cols]);
         for j in 0..self.joltage_req.len() {
             let coeffs: Vec<_> = self
                 .buttons
                 .iter()
-                .map(|btn| if btn.0.contains(&j) { 1. } else { 0. }).collect();
-            problem = problem.eq(&coeffs, self.joltage_req[j] as f64);
+                .map(|btn| if btn.0.contains(&j) { 1. } else { 0. })
+                .collect();
+            problem = problem.eq(coeffs, self.joltage_req[j] as f64);
         }
-        let solution = problem.solve().expect("unable to solve lp problem");
-        solution.objective_value().ceil() as usize
+        problem
+    }
+    fn min_buttons_joltage(&self) -> Result<usize> {
+        let solution = DefaultSolver::default().solve(&self.joltage_problem())?;
+        let presses = solution.values.iter().sum::<i64>() as usize;
+        trace_event!(
+            "joltage LP solved",
+            buttons = self.buttons.len(),
+            counters = self.joltage_req.len(),
+            presses = presses,
+        );
+        Ok(presses)
     }
 }
 
-fn parse(input: &str) -> anyhow::Result<Vec<Machine>> {
-    let light = one_of(".#").map(|c| match c {
-        '.' => false,
-        '#' => true,
-        _ => unimplemented!(),
-    });
-    let light_diagram = delimited(tag("["), many1(light), tag("]"));
-    let button = delimited(tag("("), separated_list1(tag(","), usize), tag(")")).map(Button::new);
-    let joltage_req = delimited(tag("{"), separated_list1(tag(","), usize), tag("}"));
+fn parse(input: &str) -> Result<Vec<Machine>> {
+    let light = one_of(".#").map(|c| c == '#');
+    let light_diagram = delimited(char('['), many1(light), char(']'));
+    let button = bracketed('(', unsigned, ')').map(Button::new);
+    let joltage_req = bracketed('{', unsigned, '}');
     let machine = (
         light_diagram,
         space1,
@@ -116,26 +91,28 @@ fn parse(input: &str) -> anyhow::Result<Vec<Machine>> {
         .map(|(light_diagram, _, buttons, _, joltage_req)| {
             Machine::new(light_diagram, buttons, joltage_req)
         });
-    let mut parser = separated_list1(line_ending, machine);
-    let (_rest, machines) = parser
-        .parse_complete(input)
-        .finish()
-        .map_err(|e: nom::error::Error<&str>| anyhow::anyhow!("parser error: {:?}", e))?;
-    Ok(machines)
+    parsers::run(lines(machine), input)
 }
 
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day10-input1.txt")?;
-    let mut machines = parse(input.trim())?;
-    let answer = machines
-        .iter_mut()
-        .map(|machine| machine.min_buttons_seq())
-        .sum::<usize>();
+    let machines = traced!("parse", parse(input.trim()))?;
+    let answer = traced!(
+        "part1",
+        machines
+            .iter()
+            .map(|machine| machine.min_buttons_seq())
+            .sum::<Result<usize>>()
+    )?;
     println!("part 1 answer is: {answer}");
-    let answer = machines
-        .iter()
-        .map(|machine| machine.min_buttons_joltage())
-        .sum::<usize>();
+    let answer = traced!(
+        "part2",
+        machines
+            .iter()
+            .map(|machine| machine.min_buttons_joltage())
+            .sum::<Result<usize>>()
+    )?;
     println!("part 2 answer is: {answer}");
     Ok(())
 }
@@ -144,6 +121,7 @@ fn main() -> Result<()> {
 mod tests {
 
     use super::*;
+    use aoc_2025::search::Search;
     const INPUT: &str = r#"
 [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
 [...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}
@@ -151,21 +129,40 @@ mod tests {
 "#;
     #[test]
     fn part1() -> Result<()> {
-        let mut machines = parse(INPUT.trim())?;
+        let machines = parse(INPUT.trim())?;
         let answer = machines
-            .iter_mut()
+            .iter()
             .map(|machine| machine.min_buttons_seq())
-            .sum::<usize>();
+            .sum::<Result<usize>>()?;
         assert_eq!(answer, 7);
+        assert_eq!(machines[0].buttons_to_press()?.len(), 2);
+        Ok(())
+    }
+    #[test]
+    fn part1_matches_search() -> Result<()> {
+        for machine in parse(INPUT.trim())? {
+            let press_each = |lights: &Vec<bool>| {
+                let press = |btn: &Button| {
+                    let mut next = lights.clone();
+                    btn.0.iter().for_each(|i| next[*i] = !next[*i]);
+                    next
+                };
+                machine.buttons.iter().map(press).collect::<Vec<_>>()
+            };
+            let start = vec![false; machine.lights_diagram.len()];
+            let found =
+                Search::default().bfs(start, press_each, |l| *l == machine.lights_diagram)?;
+            assert_eq!(found.map(|f| f.cost), Some(machine.min_buttons_seq()?));
+        }
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        let mut machines = parse(INPUT.trim())?;
+        let machines = parse(INPUT.trim())?;
         let answer = machines
-            .iter_mut()
+            .iter()
             .map(|machine| machine.min_buttons_joltage())
-            .sum::<usize>();
+            .sum::<Result<usize>>()?;
         assert_eq!(answer, 33);
         Ok(())
     }
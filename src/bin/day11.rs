@@ -1,122 +1,68 @@
 use anyhow::{Ok, Result};
-use itertools::Itertools;
-use petgraph::algo::toposort;
-use petgraph::graph::NodeIndex;
-use petgraph::{Graph, algo};
+use aoc_2025::dag::{PathConstraints, PathCounter};
+use aoc_2025::graph::NamedGraph;
+use aoc_2025::numeric::Num;
+use aoc_2025::parsers::{self, adjacency, lines};
+use aoc_2025::{trace, traced};
 
 use std::collections::HashMap;
 use std::fs::read_to_string;
-use std::hash::RandomState;
 
 #[derive(Debug, Clone)]
 struct AttachedDevices {
-    data: HashMap<[char; 3], Vec<[char; 3]>>,
-    graph: Graph<[char; 3], ()>,
+    data: HashMap<String, Vec<String>>,
+    graph: NamedGraph,
 }
 
 impl AttachedDevices {
-    fn new(hm: HashMap<[char; 3], Vec<[char; 3]>>) -> Self {
+    fn new(hm: HashMap<String, Vec<String>>) -> Self {
         Self {
             data: hm,
-            graph: Graph::new(),
+            graph: NamedGraph::new(),
         }
     }
     fn build_graph(mut self) -> Self {
-        self.data
-            .iter()
-            .flat_map(|(node, connected)| connected.iter().map(move |other| (node, other)))
-            .for_each(|(a, b)| {
-                let a = self
-                    .graph
-                    .node_indices()
-                    .find(|ni| self.graph[*ni] == *a)
-                    .unwrap_or_else(|| self.graph.add_node(*a));
-                let b = self
-                    .graph
-                    .node_indices()
-                    .find(|ni| self.graph[*ni] == *b)
-                    .unwrap_or_else(|| self.graph.add_node(*b));
-                self.graph.add_edge(a, b, ());
-            });
-        self
-    }
-    fn path_count_you_out(&self) -> usize {
-        let you = self
-            .graph
-            .node_indices()
-            .find(|ni| self.graph[*ni] == ['y', 'o', 'u'])
-            .expect("you not found");
-        let out = self
-            .graph
-            .node_indices()
-            .find(|ni| self.graph[*ni] == ['o', 'u', 't'])
-            .expect("out not found");
-        algo::all_simple_paths::<Vec<_>, _, RandomState>(&self.graph, you, out, 1, None).count()
-    }
-    fn path_count(&self, topo: &[NodeIndex]) -> usize {
-        let mut path_counter_map: HashMap<[char; 3], usize> =
-            HashMap::from_iter(topo.iter().map(|ni| (self.graph[*ni], 0)));
-        path_counter_map
-            .entry(self.graph[topo[0]])
-            .and_modify(|counter| *counter += 1);
-        for source_ni in topo {
-            for target_ni in self.graph.neighbors(*source_ni) {
-                let source_path_count = path_counter_map[&self.graph[*source_ni]];
-                path_counter_map
-                    .entry(self.graph[target_ni])
-                    .and_modify(|counter| *counter += source_path_count);
+        for (node, connected) in &self.data {
+            for other in connected {
+                self.graph.add_edge(node, other, ());
             }
         }
-        path_counter_map[&self.graph[*topo.last().unwrap()]]
+        self
     }
-    fn path_count_srv_out(&self) -> usize {
-        let toposorted = toposort(&self.graph, None).expect("cycle in graph");
-        let svr = toposorted
-            .iter()
-            .position(|ni| self.graph[*ni] == ['s', 'v', 'r'])
-            .expect("svr not found");
-        let fft = toposorted
-            .iter()
-            .position(|ni| self.graph[*ni] == ['f', 'f', 't'])
-            .expect("fft not found");
-        let dac = toposorted
-            .iter()
-            .position(|ni| self.graph[*ni] == ['d', 'a', 'c'])
-            .expect("dac not found");
-        let out = toposorted
-            .iter()
-            .position(|ni| self.graph[*ni] == ['o', 'u', 't'])
-            .expect("out not found");
-        self.path_count(&toposorted[svr..=fft])
-            * self.path_count(&toposorted[fft..=dac])
-            * self.path_count(&toposorted[dac..=out])
+    fn path_count_you_out(&self) -> Result<Num> {
+        let you = self.graph.index("you")?;
+        let out = self.graph.index("out")?;
+        PathCounter::new(self.graph.graph())?.count(you, out, &PathConstraints::default())
+    }
+    fn path_count_srv_out(&self) -> Result<Num> {
+        let svr = self.graph.index("svr")?;
+        let out = self.graph.index("out")?;
+        let required = [self.graph.index("fft")?, self.graph.index("dac")?];
+        PathCounter::new(self.graph.graph())?.count(svr, out, &PathConstraints::visiting(required))
     }
 }
 
-fn parse(input: &str) -> AttachedDevices {
-    let parsed = input
-        .lines()
-        .filter_map(|line| {
-            line.split_once(":").map(|(source, targets)| {
-                let s: [char; 3] = source.chars().collect_array::<3>().unwrap();
-                let t: Vec<[char; 3]> = targets
-                    .split_whitespace()
-                    .map(|target| target.chars().collect_array::<3>().unwrap())
-                    .collect();
-                (s, t)
-            })
+fn parse(input: &str) -> Result<AttachedDevices> {
+    let parsed = parsers::run(lines(adjacency), input)?
+        .into_iter()
+        .map(|(source, targets)| {
+            let t: Vec<String> = targets.into_iter().map(String::from).collect();
+            (source.to_string(), t)
         })
         .collect();
-    AttachedDevices::new(parsed)
+    Ok(AttachedDevices::new(parsed))
 }
 
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day11-input1.txt")?;
-    let devices = parse(input.trim());
-    let devices = devices.build_graph();
-    let answer = devices.path_count_you_out();
+    let devices = traced!(
+        "parse",
+        parse(input.trim()).map(|devices| devices.build_graph())
+    )?;
+    let answer = traced!("part1", devices.path_count_you_out())?;
     println!("part 1 answer is: {answer}");
-    let answer = devices.path_count_srv_out();
+    let answer = traced!("part2", devices.path_count_srv_out())?;
     println!("part 2 answer is: {answer}");
     Ok(())
 }
@@ -155,16 +101,27 @@ hhh: out
 
     #[test]
     fn part1() -> Result<()> {
-        let devices = parse(INPUT.trim());
-        let answer = devices.build_graph().path_count_you_out();
-        assert_eq!(answer, 5);
+        let devices = parse(INPUT.trim())?;
+        let answer = devices.build_graph().path_count_you_out()?;
+        assert_eq!(answer, Num::from(5_u32));
         Ok(())
     }
     #[test]
     fn part2() -> Result<()> {
-        let devices = parse(INPUT2.trim());
-        let answer = devices.build_graph().path_count_srv_out();
-        assert_eq!(answer, 2);
+        let devices = parse(INPUT2.trim())?;
+        let answer = devices.build_graph().path_count_srv_out()?;
+        assert_eq!(answer, Num::from(2_u32));
+        Ok(())
+    }
+    #[test]
+    fn part2_dac_before_fft() -> Result<()> {
+        let swapped = INPUT2
+            .replace("fft", "tmp")
+            .replace("dac", "fft")
+            .replace("tmp", "dac");
+        let devices = parse(swapped.trim())?;
+        let answer = devices.build_graph().path_count_srv_out()?;
+        assert_eq!(answer, Num::from(2_u32));
         Ok(())
     }
 }
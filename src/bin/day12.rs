@@ -1,61 +1,27 @@
-#![allow(dead_code)]
-use anyhow::{Ok, Result};
+use anyhow::{Ok, Result, ensure};
+use aoc_2025::animate::{Animation, Canvas};
+use aoc_2025::dlx::Dlx;
+use aoc_2025::parsers::{self, blank_line, grid, lines, sections, unsigned};
+use aoc_2025::polyomino::Polyomino;
+use aoc_2025::render::{GREY, Scene, palette, render_target};
+use aoc_2025::{trace, trace_event, traced};
+use glam::IVec2;
 use itertools::Itertools;
+use nom::Parser;
 use nom::bytes::complete::tag;
-use nom::character::complete::{i32, line_ending, one_of, space1, usize};
-use nom::multi::{many1, separated_list1};
-use nom::sequence::{delimited, separated_pair};
-use nom::{Finish, Parser};
-use std::collections::HashSet;
+use nom::character::complete::{char, line_ending, space1};
+use nom::multi::separated_list1;
+use nom::sequence::{preceded, separated_pair};
 use std::fmt::Display;
 use std::fs::read_to_string;
+use std::path::Path;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Shape([[u8; 3]; 3]);
-
-impl Shape {
-    fn new(arr: [u8; 9]) -> Self {
-        Self([
-            [arr[0], arr[1], arr[2]],
-            [arr[3], arr[4], arr[5]],
-            [arr[6], arr[7], arr[8]],
-        ])
-    }
-    fn rot_left(&self) -> Self {
-        let mat = self.0;
-        let left = [
-            [mat[0][2], mat[1][2], mat[2][2]],
-            [mat[0][1], mat[1][1], mat[2][1]],
-            [mat[0][0], mat[1][0], mat[2][0]],
-        ];
-        Self(left)
-    }
-    fn rot_right(&self) -> Self {
-        self.rot_left().rot_left().rot_left()
-    }
-    fn rot_180(&self) -> Self {
-        self.rot_left().rot_left()
-    }
-    fn flip_vert(&self) -> Self {
-        let mat = self.0;
-        let flip_vert = [
-            [mat[0][2], mat[0][1], mat[0][0]],
-            [mat[1][2], mat[1][1], mat[1][0]],
-            [mat[2][2], mat[2][1], mat[2][0]],
-        ];
-        Self(flip_vert)
-    }
-    fn flip_hor(&self) -> Self {
-        let mat = self.0;
-        let flip_hor = [mat[2], mat[1], mat[0]];
-        Self(flip_hor)
-    }
-}
+const DARK: [u8; 3] = [40, 40, 52];
 
 #[derive(Debug, Clone, Hash)]
 struct Region {
-    shape_ids: [usize; 6],
-    state: Vec<Vec<u8>>,
+    shape_ids: Vec<usize>,
+    state: Vec<Vec<usize>>,
 }
 
 impl Display for Region {
@@ -71,147 +37,300 @@ impl Display for Region {
 }
 impl Region {
     fn new(shape_ids: Vec<usize>, w: usize, h: usize) -> Self {
-        let shape_ids = shape_ids
-            .into_iter()
-            .collect_array::<6>()
-            .expect("parse region failed");
         Self {
             shape_ids,
             state: vec![vec![0; w]; h],
         }
     }
-    fn add_shape(mut self, shape: Shape, r: usize, c: usize) -> Option<Self> {
-        for i in 0..3 {
-            for j in 0..3 {
-                self.state[r + i][c + j] += shape.0[i][j];
-                if self.state[r + i][c + j] == 2 {
-                    return None;
+    fn width(&self) -> usize {
+        self.state[0].len()
+    }
+    fn height(&self) -> usize {
+        self.state.len()
+    }
+    /// Exact packing as an exact cover problem: every shape column must be used
+    /// `shape_ids[i]` times, cells are secondary columns and may stay empty.
+    /// Returns the region with cells numbered by piece, starting from 1.
+    fn pack(&self, shapes: &[Polyomino]) -> Option<Self> {
+        let (mut dlx, placements) = self.exact_cover(shapes);
+        let rows = dlx.solve()?;
+        for (piece, row) in rows.iter().enumerate() {
+            trace_event!("piece placed", piece = piece + 1, cells = placements[*row]);
+        }
+        Some(self.with_pieces(&placements, &rows))
+    }
+    /// Like `pack`, `progress` sees the placements and chosen rows of every
+    /// partial packing and can give up, `with_pieces` turns them into a region.
+    fn pack_with_progress(
+        &self,
+        shapes: &[Polyomino],
+        mut progress: impl FnMut(&[Vec<usize>], &[usize]) -> bool,
+    ) -> Option<Self> {
+        let (mut dlx, placements) = self.exact_cover(shapes);
+        let rows = dlx.solve_with_progress(|rows| progress(&placements, rows))?;
+        Some(self.with_pieces(&placements, &rows))
+    }
+    /// Exact cover matrix and the cells covered by every one of its rows.
+    fn exact_cover(&self, shapes: &[Polyomino]) -> (Dlx, Vec<Vec<usize>>) {
+        let (w, h) = (self.width(), self.height());
+        let primary = self.shape_ids.len();
+        let mut dlx = Dlx::with_multiplicities(&self.shape_ids, w * h);
+        let mut placements = vec![];
+        for (shape_id, shape) in shapes.iter().enumerate().take(primary) {
+            if self.shape_ids[shape_id] == 0 {
+                continue;
+            }
+            for orientation in shape.orientations() {
+                if orientation.height() > h || orientation.width() > w {
+                    continue;
+                }
+                for (r, c) in (0..=h - orientation.height())
+                    .cartesian_product(0..=w - orientation.width())
+                {
+                    let cells: Vec<usize> = orientation
+                        .cells()
+                        .iter()
+                        .map(|cell| (r + cell.y as usize) * w + c + cell.x as usize)
+                        .collect();
+                    let columns: Vec<usize> = std::iter::once(shape_id)
+                        .chain(cells.iter().map(|cell| primary + cell))
+                        .collect();
+                    dlx.add_row(&columns);
+                    placements.push(cells);
                 }
             }
         }
-        Some(self)
+        (dlx, placements)
     }
-
-    fn shapes_fit(&self, shapes: &[Shape]) -> bool {
-        let mut queue = vec![self.clone()];
-        let w = self.state[0].len();
-        let h = self.state.len();
-
-        while let Some(mut cur) = queue.pop() {
-            let shape_ids = cur.shape_ids;
-            if shape_ids.iter().all(|count| *count == 0) {
-                eprintln!("{cur}");
-                return true;
+    fn with_pieces(&self, placements: &[Vec<usize>], rows: &[usize]) -> Self {
+        let w = self.width();
+        let mut packed = self.clone();
+        for (piece, row) in rows.iter().enumerate() {
+            for cell in &placements[*row] {
+                packed.state[cell / w][cell % w] = piece + 1;
             }
-            let shape_id = shape_ids.iter().position(|count| *count > 0).unwrap();
-            cur.shape_ids[shape_id] -= 1;
-            let new_shapes = HashSet::from([
-                shapes[shape_id],
-                shapes[shape_id].rot_left(),
-                shapes[shape_id].rot_right(),
-                shapes[shape_id].rot_180(),
-                shapes[shape_id].flip_hor(),
-                shapes[shape_id].flip_vert(),
-            ]);
-            queue.extend(new_shapes.iter().flat_map(|shape| {
-                (0..=h - 3)
-                    .cartesian_product(0..=w - 3)
-                    .filter(|(r, c)| cur.state[*r][*c] == 0)
-                    .filter_map(|(r, c)| cur.clone().add_shape(*shape, r, c))
-            }));
         }
-        false
+        packed
+    }
+    fn shapes_fit(&self, shapes: &[Polyomino]) -> bool {
+        self.pack(shapes).is_some()
     }
 }
 #[derive(Debug, Clone)]
 struct Tetris {
-    shapes: Vec<Shape>,
+    shapes: Vec<Polyomino>,
     regions: Vec<Region>,
 }
 
 impl Tetris {
     fn stats(&self) {
-        let shapes_occupied_space: Vec<_> = self
-            .shapes
-            .iter()
-            .map(|shape| shape.0.iter().flatten().filter(|h| **h == 1).count())
-            .collect();
         for region in &self.regions {
-            let occupied_total: usize = region
+            trace_event!(
+                "region",
+                width = region.width(),
+                height = region.height(),
+                shape_ids = region.shape_ids,
+                occupied = self.pieces_area(region),
+                total = region.width() * region.height(),
+            );
+        }
+    }
+    fn pieces_area(&self, region: &Region) -> usize {
+        region
+            .shape_ids
+            .iter()
+            .zip(&self.shapes)
+            .map(|(count, shape)| count * shape.area())
+            .sum()
+    }
+    /// Necessary condition: the pieces do not cover more cells than the region has.
+    fn fit_heuristic(&self, region: &Region) -> bool {
+        self.pieces_area(region) <= region.width() * region.height()
+    }
+    /// Box large enough for every piece used in the region.
+    fn box_size(&self, region: &Region) -> (usize, usize) {
+        let used = || {
+            region
                 .shape_ids
                 .iter()
-                .enumerate()
-                .map(|(i, c)| shapes_occupied_space[i] * c)
-                .sum();
-            println!(
-                "{}x{} {:?}: occupied {occupied_total} vs total {}",
-                region.state[0].len(),
-                region.state.len(),
-                region.shape_ids,
-                region.state[0].len() * region.state.len()
+                .zip(&self.shapes)
+                .filter(|(count, _)| **count > 0)
+        };
+        let box_w = used().map(|(_, shape)| shape.width()).max().unwrap_or(1);
+        let box_h = used().map(|(_, shape)| shape.height()).max().unwrap_or(1);
+        (box_w, box_h)
+    }
+    /// Sufficient condition: every piece gets a bounding box of its own.
+    fn fits_side_by_side(&self, region: &Region) -> bool {
+        let (box_w, box_h) = self.box_size(region);
+        let pieces: usize = region.shape_ids.iter().sum();
+        (region.width() / box_w) * (region.height() / box_h) >= pieces
+    }
+    /// Every piece in the first free box of a grid of equal bounding boxes.
+    fn side_by_side(&self, region: &Region) -> Region {
+        let (box_w, box_h) = self.box_size(region);
+        let per_row = region.width() / box_w;
+        let mut packed = region.clone();
+        let pieces = region
+            .shape_ids
+            .iter()
+            .enumerate()
+            .flat_map(|(shape_id, count)| std::iter::repeat_n(shape_id, *count));
+        for (piece, shape_id) in pieces.enumerate() {
+            let corner = IVec2::new(
+                (piece % per_row * box_w) as i32,
+                (piece / per_row * box_h) as i32,
             );
+            for cell in self.shapes[shape_id].cells() {
+                let p = corner + *cell;
+                packed.state[p.y as usize][p.x as usize] = piece + 1;
+            }
         }
+        packed
     }
-    fn fit_heuristic(&self) -> usize {
-        let shapes_occupied_space: Vec<_> = self
-            .shapes
-            .iter()
-            .map(|shape| shape.0.iter().flatten().filter(|h| **h == 1).count())
-            .collect();
+    /// A packing of the region, cells numbered by piece, if the pieces fit.
+    fn packing(&self, region: &Region) -> Option<Region> {
+        if !self.fit_heuristic(region) {
+            None
+        } else if self.fits_side_by_side(region) {
+            Some(self.side_by_side(region))
+        } else {
+            region.pack(&self.shapes)
+        }
+    }
+    fn region_fits(&self, region: &Region) -> bool {
+        self.fit_heuristic(region)
+            && (self.fits_side_by_side(region) || region.shapes_fit(&self.shapes))
+    }
+    fn fit_count(&self) -> usize {
         self.regions
             .iter()
-            .filter(|region| {
-                region
-                    .shape_ids
-                    .iter()
-                    .enumerate()
-                    .map(|(i, c)| shapes_occupied_space[i] * c)
-                    .sum::<usize>()
-                    < region.state[0].len() * region.state.len()
-            })
+            .filter(|region| self.region_fits(region))
             .count()
     }
 }
 
-fn parse(input: &str) -> anyhow::Result<Tetris> {
-    let shape_line = many1(one_of(".#").map(|c| match c {
-        '.' => 0_u8,
-        '#' => 1,
-        _ => unimplemented!(),
-    }));
-    let shape = delimited(
-        i32.and(tag(":").and(line_ending)),
-        separated_list1(line_ending, shape_line),
-        line_ending,
-    )
-    .map(|shape| {
-        let arr = shape
-            .iter()
-            .flatten()
-            .copied()
-            .collect_array::<9>()
-            .expect("parse shape failed");
-        Shape::new(arr)
-    });
-    let shapes = separated_list1(line_ending, shape);
-    let dims = separated_pair(usize, tag("x"), usize);
-    let shape_ids = separated_list1(space1, usize);
-    let region =
-        separated_pair(dims, tag(": "), shape_ids).map(|((w, h), ids)| Region::new(ids, w, h));
-    let regions = separated_list1(line_ending, region);
-    let mut parser = separated_pair(shapes, line_ending, regions);
-    let (_rest, (shapes, regions)) = parser
-        .parse_complete(input)
-        .finish()
-        .map_err(|e: nom::error::Error<&str>| anyhow::anyhow!("parser error: {:?}", e))?;
+fn draw(region: &Region) -> Canvas {
+    let mut canvas = Canvas::new(region.width(), region.height());
+    for (y, row) in region.state.iter().enumerate() {
+        for (x, piece) in row.iter().enumerate() {
+            let p = IVec2::new(x as i32, y as i32);
+            match piece {
+                0 => canvas.set(p, '.', GREY),
+                _ => canvas.set(p, '#', palette(*piece)),
+            }
+        }
+    }
+    canvas
+}
+
+/// Search progress region by region, every partial packing is a frame.
+fn animate(tree_farm: &Tetris, animation: &mut Animation) -> Result<()> {
+    let count = tree_farm.regions.len();
+    for (i, region) in tree_farm.regions.iter().enumerate() {
+        let name = format!(
+            "region {}/{count} {}x{}",
+            i + 1,
+            region.width(),
+            region.height()
+        );
+        if !tree_farm.fit_heuristic(region) {
+            animation.frame(&format!("{name}: pieces too large"), || draw(region))?;
+        } else if tree_farm.fits_side_by_side(region) {
+            let packed = tree_farm.side_by_side(region);
+            animation.frame(&format!("{name}: side by side"), || draw(&packed))?;
+        } else {
+            let mut nodes = 0;
+            let mut failed = None;
+            let packed = region.pack_with_progress(&tree_farm.shapes, |placements, rows| {
+                nodes += 1;
+                let title = format!("{name}: searching, node {nodes}");
+                let partial = || draw(&region.with_pieces(placements, rows));
+                failed = animation.frame(&title, partial).err();
+                failed.is_none()
+            });
+            if let Some(err) = failed {
+                return Err(err);
+            }
+            let outcome = if packed.is_some() { "packed" } else { "no fit" };
+            let shown = packed.unwrap_or_else(|| region.clone());
+            let title = format!("{name}: {outcome} after {nodes} nodes");
+            animation.frame(&title, || draw(&shown))?;
+        }
+    }
+    Ok(())
+}
+
+/// Packed regions side by side, pieces told apart by colour.
+fn render(tree_farm: &Tetris, path: &Path) -> Result<()> {
+    const SHOWN: usize = 16;
+    let packed: Vec<Region> = tree_farm
+        .regions
+        .iter()
+        .filter_map(|region| tree_farm.packing(region))
+        .take(SHOWN)
+        .collect();
+    let per_row = (packed.len() as f64).sqrt().ceil().max(1.0) as usize;
+    let cell_w = packed.iter().map(Region::width).max().unwrap_or(1) + 1;
+    let cell_h = packed.iter().map(Region::height).max().unwrap_or(1) + 1;
+    let rows = packed.len().div_ceil(per_row).max(1);
+    let mut scene = Scene::new((per_row * cell_w + 1) as i32, (rows * cell_h + 1) as i32);
+    for (i, region) in packed.iter().enumerate() {
+        let corner = IVec2::new(
+            (i % per_row * cell_w + 1) as i32,
+            (i / per_row * cell_h + 1) as i32,
+        );
+        let size = IVec2::new(region.width() as i32, region.height() as i32);
+        scene.rect(corner, size, DARK);
+        for (y, row) in region.state.iter().enumerate() {
+            for (x, piece) in row.iter().enumerate().filter(|(_, piece)| **piece > 0) {
+                scene.cell(corner + IVec2::new(x as i32, y as i32), palette(*piece));
+            }
+        }
+    }
+    scene.save(path)
+}
+
+fn parse(input: &str) -> Result<Tetris> {
+    let cell = |c| matches!(c, '.' | '#').then_some(c == '#');
+    let shape = preceded((unsigned::<usize>, char(':'), line_ending), grid(cell))
+        .map(|shape| Polyomino::from_grid(&shape));
+    let dims = separated_pair(unsigned, char('x'), unsigned);
+    let shape_ids = separated_list1(space1, unsigned);
+    let region = separated_pair(dims, tag(": "), shape_ids);
+    let (shapes, regions) = parsers::run(
+        separated_pair(sections(shape), blank_line, lines(region)),
+        input,
+    )?;
+    let regions = regions
+        .into_iter()
+        .map(|((w, h), ids)| {
+            ensure!(w > 0 && h > 0, "region {w}x{h} is empty");
+            ensure!(
+                ids.len() <= shapes.len(),
+                "region {w}x{h} counts {} shapes but only {} are defined",
+                ids.len(),
+                shapes.len()
+            );
+            Ok(Region::new(ids, w, h))
+        })
+        .collect::<Result<_>>()?;
     Ok(Tetris { shapes, regions })
 }
 
 fn main() -> Result<()> {
+    trace::init();
     let input = read_to_string("inputs/day12-input1.txt")?;
-    let tree_farm = parse(input.trim())?;
-    let answer = tree_farm.fit_heuristic();
+    let tree_farm = traced!("parse", parse(input.trim()))?;
+    tree_farm.stats();
+    let answer = traced!("part1", tree_farm.fit_count());
     println!("part 1 answer is: {answer}");
+    if let Some(path) = render_target()? {
+        render(&tree_farm, &path)?;
+    }
+    if let Some(mut animation) = Animation::from_args()? {
+        animate(&tree_farm, &mut animation)?;
+    }
     // let answer = 0
     // println!("part 2 answer is: {answer}");
     Ok(())
@@ -259,9 +378,28 @@ mod tests {
     #[test]
     fn part1() -> Result<()> {
         let tree_farm = parse(INPUT.trim())?;
-        dbg!(tree_farm);
-        let answer = 0;
-        assert_eq!(answer, 7);
+        let answer = tree_farm.fit_count();
+        assert_eq!(answer, 2);
+        Ok(())
+    }
+    #[test]
+    fn rejects_bad_regions() {
+        for bad in ["4x4: 0 0 0 0 2 0 1", "4x0: 0 0 0 0 2 0", "0x4: 0 0 0 0 2 0"] {
+            let input = INPUT.trim().replace("4x4: 0 0 0 0 2 0", bad);
+            assert!(parse(&input).is_err(), "{bad}");
+        }
+    }
+    #[test]
+    fn packings_cover_their_pieces() -> Result<()> {
+        let tree_farm = parse(INPUT.trim())?;
+        let mut roomy = tree_farm.regions[0].clone();
+        roomy.state = vec![vec![0; 6]; 6];
+        for region in [&tree_farm.regions[0], &roomy, &tree_farm.regions[1]] {
+            let packed = tree_farm.packing(region).expect("fits");
+            let covered = packed.state.iter().flatten().filter(|p| **p > 0).count();
+            assert_eq!(covered, tree_farm.pieces_area(region));
+        }
+        assert!(tree_farm.packing(&tree_farm.regions[2]).is_none());
         Ok(())
     }
     #[test]
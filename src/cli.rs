@@ -0,0 +1,50 @@
+//! Optional command line flags of the solutions, `--name` or `--name <value>`.
+use anyhow::{Result, anyhow};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Arguments after the program name.
+pub fn args() -> Vec<String> {
+    std::env::args().skip(1).collect()
+}
+
+pub fn flag(args: &[String], name: &str) -> bool {
+    args.iter().any(|arg| arg == name)
+}
+
+/// Value following the flag `name`, `None` if the flag is not given.
+pub fn value<T>(args: &[String], name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Some(i) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+    let value = args.get(i + 1).ok_or(anyhow!("{name} needs a value"))?;
+    value
+        .parse()
+        .map(Some)
+        .map_err(|err| anyhow!("bad {name} {value}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_and_values() -> Result<()> {
+        let args: Vec<String> = ["--step", "--fps", "2.5", "--size"].map(String::from).into();
+        assert!(flag(&args, "--step"));
+        assert!(!flag(&args, "--animate"));
+        assert_eq!(value::<f64>(&args, "--fps")?, Some(2.5));
+        assert_eq!(value::<i64>(&args, "--start")?, None);
+        assert_eq!(
+            value::<i64>(&args, "--size").unwrap_err().to_string(),
+            "--size needs a value"
+        );
+        let err = value::<u32>(&args, "--fps").unwrap_err();
+        assert_eq!(err.to_string(), "bad --fps 2.5: invalid digit found in string");
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+//! Path counting over directed acyclic graphs with required and forbidden nodes.
+use crate::numeric::{CheckedArith, Num};
+use anyhow::{Result, anyhow, bail};
+use num::{One, Zero};
+use petgraph::Graph;
+use petgraph::algo::toposort;
+use petgraph::graph::NodeIndex;
+use std::collections::HashSet;
+
+/// Most waypoints a query may require, the DP keeps `2^n` states per node.
+pub const MAX_WAYPOINTS: usize = 16;
+
+#[derive(Debug, Clone, Default)]
+pub struct PathConstraints {
+    required: Vec<NodeIndex>,
+    ordered: bool,
+    forbidden: HashSet<NodeIndex>,
+}
+
+impl PathConstraints {
+    /// Paths that visit all of `nodes`, in any order.
+    pub fn visiting(nodes: impl IntoIterator<Item = NodeIndex>) -> Self {
+        Self {
+            required: nodes.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+    /// Paths that visit all of `nodes` in exactly the given order.
+    pub fn visiting_in_order(nodes: impl IntoIterator<Item = NodeIndex>) -> Self {
+        Self {
+            required: nodes.into_iter().collect(),
+            ordered: true,
+            ..Self::default()
+        }
+    }
+    /// Additionally excludes paths through any of `nodes`.
+    pub fn avoiding(mut self, nodes: impl IntoIterator<Item = NodeIndex>) -> Self {
+        self.forbidden.extend(nodes);
+        self
+    }
+    fn state_count(&self) -> usize {
+        if self.ordered {
+            self.required.len() + 1
+        } else {
+            1 << self.required.len()
+        }
+    }
+    /// State after entering `node` from `state`, `None` if the order is broken.
+    /// Unordered states are bitmasks of visited waypoints, ordered states count
+    /// the waypoints visited so far.
+    fn step(&self, state: usize, node: NodeIndex) -> Option<usize> {
+        let Some(k) = self.required.iter().position(|r| *r == node) else {
+            return Some(state);
+        };
+        if !self.ordered {
+            Some(state | 1 << k)
+        } else if k == state {
+            Some(state + 1)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PathCounter<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    topo: Vec<NodeIndex>,
+    // position of every node in `topo`
+    rank: Vec<usize>,
+}
+
+impl<'a, N, E> PathCounter<'a, N, E> {
+    pub fn new(graph: &'a Graph<N, E>) -> Result<Self> {
+        let topo = toposort(graph, None).map_err(|cycle| {
+            anyhow!("graph has a cycle through node {}", cycle.node_id().index())
+        })?;
+        let mut rank = vec![0; graph.node_count()];
+        for (i, ni) in topo.iter().enumerate() {
+            rank[ni.index()] = i;
+        }
+        Ok(Self { graph, topo, rank })
+    }
+    /// Number of distinct paths from `source` to `target` satisfying `constraints`.
+    pub fn count(
+        &self,
+        source: NodeIndex,
+        target: NodeIndex,
+        constraints: &PathConstraints,
+    ) -> Result<Num> {
+        if constraints.required.len() > MAX_WAYPOINTS {
+            bail!(
+                "at most {MAX_WAYPOINTS} waypoints supported, got {}",
+                constraints.required.len()
+            );
+        }
+        let forbidden = |ni: &NodeIndex| constraints.forbidden.contains(ni);
+        if forbidden(&source) || forbidden(&target) {
+            return Ok(Num::zero());
+        }
+        let states = constraints.state_count();
+        let (from, to) = (self.rank[source.index()], self.rank[target.index()]);
+        if from > to {
+            return Ok(Num::zero());
+        }
+        let mut counts = vec![Num::zero(); (to - from + 1) * states];
+        if let Some(state) = constraints.step(0, source) {
+            counts[state] = Num::one();
+        }
+        for (offset, node) in self.topo[from..to].iter().enumerate() {
+            for state in 0..states {
+                // finished nodes are never read again, only `target` is
+                let paths = std::mem::take(&mut counts[offset * states + state]);
+                if paths.is_zero() {
+                    continue;
+                }
+                for next in self.graph.neighbors(*node).filter(|ni| !forbidden(ni)) {
+                    let rank = self.rank[next.index()];
+                    if rank > to {
+                        continue;
+                    }
+                    let Some(next_state) = constraints.step(state, next) else {
+                        continue;
+                    };
+                    let slot = &mut counts[(rank - from) * states + next_state];
+                    *slot = slot.try_add(&paths)?;
+                }
+            }
+        }
+        Ok(counts.swap_remove((to - from) * states + states - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(n: u32) -> Num {
+        Num::from(n)
+    }
+
+    // a -> b -> d, a -> c -> d, b -> c, d -> e
+    fn diamond() -> (Graph<(), ()>, Vec<NodeIndex>) {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        for (a, b) in [(0, 1), (0, 2), (1, 3), (2, 3), (1, 2), (3, 4)] {
+            graph.add_edge(n[a], n[b], ());
+        }
+        (graph, n)
+    }
+
+    #[test]
+    fn waypoints_any_and_fixed_order() -> Result<()> {
+        let (graph, n) = diamond();
+        let counter = PathCounter::new(&graph)?;
+        let free = PathConstraints::default();
+        assert_eq!(counter.count(n[0], n[4], &free)?, num(3));
+        let any = PathConstraints::visiting([n[2], n[1]]);
+        assert_eq!(counter.count(n[0], n[4], &any)?, num(1));
+        let wrong_order = PathConstraints::visiting_in_order([n[2], n[1]]);
+        assert_eq!(counter.count(n[0], n[4], &wrong_order)?, num(0));
+        let right_order = PathConstraints::visiting_in_order([n[1], n[2]]);
+        assert_eq!(counter.count(n[0], n[4], &right_order)?, num(1));
+        Ok(())
+    }
+    #[test]
+    fn forbidden_nodes() -> Result<()> {
+        let (graph, n) = diamond();
+        let counter = PathCounter::new(&graph)?;
+        let avoid = PathConstraints::default().avoiding([n[1]]);
+        assert_eq!(counter.count(n[0], n[4], &avoid)?, num(1));
+        let through_c = PathConstraints::visiting([n[2]]).avoiding([n[1]]);
+        assert_eq!(counter.count(n[0], n[3], &through_c)?, num(1));
+        assert_eq!(
+            counter.count(n[4], n[0], &PathConstraints::default())?,
+            num(0)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+//! Circular counter with a runtime modulus, like a safe's combination dial.
+use anyhow::{Result, bail};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dial {
+    size: i64,
+    position: i64,
+}
+
+impl Dial {
+    /// Dial with positions `0..size`, pointing at `position` taken modulo `size`.
+    pub fn new(size: i64, position: i64) -> Result<Self> {
+        if size <= 0 {
+            bail!("dial size must be positive, got {size}");
+        }
+        Ok(Self {
+            size,
+            position: position.rem_euclid(size),
+        })
+    }
+    pub fn size(&self) -> i64 {
+        self.size
+    }
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+    pub fn at_zero(&self) -> bool {
+        self.position == 0
+    }
+    /// Position after turning by `amount` clicks, positive towards higher numbers.
+    pub fn peek(&self, amount: i64) -> i64 {
+        (self.position as i128 + amount as i128).rem_euclid(self.size as i128) as i64
+    }
+    /// Clicks of a turn by `amount` that land on zero. The starting position
+    /// does not count, the final one does.
+    pub fn zeros_passed(&self, amount: i64) -> u64 {
+        let (size, start) = (self.size as i128, self.position as i128);
+        let end = start + amount as i128;
+        // multiples of `size` in (start, end] or [end, start)
+        let passed = if amount >= 0 {
+            end.div_euclid(size) - start.div_euclid(size)
+        } else {
+            (start - 1).div_euclid(size) - (end - 1).div_euclid(size)
+        };
+        passed as u64
+    }
+    /// Clicks into a turn by `amount` at which the dial first points at zero,
+    /// later zeros follow every `size` clicks.
+    pub fn first_zero(&self, amount: i64) -> Option<i64> {
+        let to_zero = match (self.position, amount >= 0) {
+            (0, _) => self.size,
+            (position, true) => self.size - position,
+            (position, false) => position,
+        };
+        (to_zero as u64 <= amount.unsigned_abs()).then_some(to_zero)
+    }
+    /// Turns by `amount` clicks and returns how often the dial pointed at zero
+    /// on the way, see [`Dial::zeros_passed`].
+    pub fn rotate(&mut self, amount: i64) -> u64 {
+        let passed = self.zeros_passed(amount);
+        self.position = self.peek(amount);
+        passed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_both_ways() -> Result<()> {
+        let mut dial = Dial::new(100, 50)?;
+        assert_eq!(dial.rotate(-68), 1);
+        assert_eq!(dial.position(), 82);
+        assert_eq!(dial.rotate(18), 1);
+        assert!(dial.at_zero());
+        // leaving zero does not count, coming back does
+        assert_eq!(dial.rotate(-100), 1);
+        assert_eq!(dial.rotate(-5), 0);
+        assert_eq!(dial.rotate(1005), 11);
+        assert_eq!(dial.position(), 0);
+        assert_eq!(Dial::new(100, -1)?.position(), 99);
+        assert!(Dial::new(0, 0).is_err());
+        Ok(())
+    }
+    #[test]
+    fn matches_click_by_click() -> Result<()> {
+        for size in [1, 2, 7, 100] {
+            for start in 0..size {
+                for amount in -3 * size..=3 * size {
+                    let dial = Dial::new(size, start)?;
+                    let step = amount.signum();
+                    let clicks = (1..=amount.abs())
+                        .filter(|k| (start + k * step).rem_euclid(size) == 0)
+                        .count() as u64;
+                    assert_eq!(dial.zeros_passed(amount), clicks, "{size} {start} {amount}");
+                    assert_eq!(dial.peek(amount), (start + amount).rem_euclid(size));
+                    let first =
+                        (1..=amount.abs()).find(|k| (start + k * step).rem_euclid(size) == 0);
+                    assert_eq!(dial.first_zero(amount), first);
+                }
+            }
+        }
+        let mut huge = Dial::new(i64::MAX, i64::MAX - 1)?;
+        assert_eq!(huge.rotate(i64::MAX), 1);
+        assert_eq!(huge.position(), i64::MAX - 1);
+        Ok(())
+    }
+}
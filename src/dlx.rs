@@ -0,0 +1,348 @@
+//! Exact cover solver using Knuth's Dancing Links (Algorithm X).
+//!
+//! Primary columns must be covered exactly `multiplicity` times (once by default),
+//! secondary columns at most once. Rows sharing a primary column of multiplicity
+//! greater than one are picked in list order only, so identical items do not
+//! produce permuted duplicates of the same solution.
+
+#[derive(Debug, Clone)]
+pub struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    // column header of every node, headers point to themselves
+    col: Vec<usize>,
+    // row id of every node, unused for headers
+    row: Vec<usize>,
+    size: Vec<usize>,
+    remaining: Vec<usize>,
+    primary: usize,
+    rows: usize,
+}
+
+const ROOT: usize = 0;
+
+impl Dlx {
+    pub fn new(primary: usize, secondary: usize) -> Self {
+        Self::with_multiplicities(&vec![1; primary], secondary)
+    }
+    /// Primary column `i` must be covered exactly `multiplicities[i]` times.
+    pub fn with_multiplicities(multiplicities: &[usize], secondary: usize) -> Self {
+        let primary = multiplicities.len();
+        let headers = primary + secondary + 1;
+        let mut dlx = Self {
+            left: (0..headers).collect(),
+            right: (0..headers).collect(),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            col: (0..headers).collect(),
+            row: vec![usize::MAX; headers],
+            size: vec![0; headers],
+            remaining: [0]
+                .into_iter()
+                .chain(multiplicities.iter().copied())
+                .collect(),
+            primary,
+            rows: 0,
+        };
+        dlx.remaining.resize(headers, 1);
+        // only primary columns with something left to cover join the header list
+        let mut last = ROOT;
+        for c in (1..=primary).filter(|c| dlx.remaining[*c] > 0) {
+            dlx.right[last] = c;
+            dlx.left[c] = last;
+            last = c;
+        }
+        dlx.right[last] = ROOT;
+        dlx.left[ROOT] = last;
+        dlx
+    }
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+    /// Adds a row covering the given columns, numbered primary first then secondary,
+    /// and returns its id.
+    pub fn add_row(&mut self, columns: &[usize]) -> usize {
+        let id = self.rows;
+        self.rows += 1;
+        let first = self.col.len();
+        for (k, &c) in columns.iter().enumerate() {
+            let header = c + 1;
+            assert!(header < self.size.len(), "column {c} out of range");
+            let node = first + k;
+            self.col.push(header);
+            self.row.push(id);
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let above = self.up[header];
+            self.down[above] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+            self.left.push(if k == 0 { node } else { node - 1 });
+            self.right.push(first);
+            if k > 0 {
+                self.right[node - 1] = node;
+                self.left[first] = node;
+            }
+        }
+        id
+    }
+    /// First solution found, as row ids.
+    pub fn solve(&mut self) -> Option<Vec<usize>> {
+        self.solutions(1).pop()
+    }
+    /// Like [`Dlx::solve`], `progress` sees the partial selection at every search
+    /// node and gives up the search by returning `false`.
+    pub fn solve_with_progress(
+        &mut self,
+        mut progress: impl FnMut(&[usize]) -> bool,
+    ) -> Option<Vec<usize>> {
+        let mut found = None;
+        self.run(
+            &mut |rows| {
+                found = Some(rows.to_vec());
+                false
+            },
+            &mut progress,
+        );
+        found
+    }
+    /// Up to `limit` solutions, each as row ids in selection order.
+    pub fn solutions(&mut self, limit: usize) -> Vec<Vec<usize>> {
+        let mut found = vec![];
+        if limit > 0 {
+            self.run(
+                &mut |rows| {
+                    found.push(rows.to_vec());
+                    found.len() < limit
+                },
+                &mut |_| true,
+            );
+        }
+        found
+    }
+    pub fn count_solutions(&mut self) -> usize {
+        let mut count = 0;
+        self.run(
+            &mut |_| {
+                count += 1;
+                true
+            },
+            &mut |_| true,
+        );
+        count
+    }
+    fn run(
+        &mut self,
+        visit: &mut dyn FnMut(&[usize]) -> bool,
+        progress: &mut dyn FnMut(&[usize]) -> bool,
+    ) {
+        // rows needing a primary column that must not be used can never be picked
+        let unused: Vec<usize> = (1..=self.primary)
+            .filter(|c| self.remaining[*c] == 0)
+            .collect();
+        unused.iter().for_each(|c| self.cover(*c));
+        self.search(&mut vec![], visit, progress);
+        unused.iter().rev().for_each(|c| self.uncover(*c));
+    }
+    /// Returns `false` once `visit` or `progress` asks to stop.
+    fn search(
+        &mut self,
+        partial: &mut Vec<usize>,
+        visit: &mut dyn FnMut(&[usize]) -> bool,
+        progress: &mut dyn FnMut(&[usize]) -> bool,
+    ) -> bool {
+        if !progress(partial) {
+            return false;
+        }
+        if self.right[ROOT] == ROOT {
+            return visit(partial);
+        }
+        let Some(c) = self.choose_column() else {
+            return true;
+        };
+        let mut proceed = true;
+        if self.remaining[c] == 1 {
+            self.cover(c);
+            let mut r = self.down[c];
+            while r != c && proceed {
+                partial.push(self.row[r]);
+                self.select_others(r);
+                proceed = self.search(partial, visit, progress);
+                self.unselect_others(r);
+                partial.pop();
+                r = self.down[r];
+            }
+            self.uncover(c);
+        } else {
+            let mut hidden = vec![];
+            let mut r = self.down[c];
+            while r != c && proceed {
+                let next = self.down[r];
+                self.hide_row(r);
+                hidden.push(r);
+                self.remaining[c] -= 1;
+                partial.push(self.row[r]);
+                self.select_others(r);
+                proceed = self.search(partial, visit, progress);
+                self.unselect_others(r);
+                partial.pop();
+                self.remaining[c] += 1;
+                r = next;
+            }
+            for r in hidden.into_iter().rev() {
+                self.unhide_row(r);
+            }
+        }
+        proceed
+    }
+    /// Active primary column with the fewest spare rows, `None` if one cannot be
+    /// covered anymore.
+    fn choose_column(&self) -> Option<usize> {
+        let mut best = None;
+        let mut best_slack = usize::MAX;
+        let mut c = self.right[ROOT];
+        while c != ROOT {
+            let slack = self.size[c].checked_sub(self.remaining[c])?;
+            if slack < best_slack {
+                best_slack = slack;
+                best = Some(c);
+            }
+            c = self.right[c];
+        }
+        best
+    }
+    fn select_others(&mut self, r: usize) {
+        let mut j = self.right[r];
+        while j != r {
+            self.take(self.col[j]);
+            j = self.right[j];
+        }
+    }
+    fn unselect_others(&mut self, r: usize) {
+        let mut j = self.left[r];
+        while j != r {
+            self.untake(self.col[j]);
+            j = self.left[j];
+        }
+    }
+    /// Uses up one cover of column `c`, removing it once nothing is left.
+    fn take(&mut self, c: usize) {
+        self.remaining[c] -= 1;
+        if self.remaining[c] == 0 {
+            self.cover(c);
+        }
+    }
+    fn untake(&mut self, c: usize) {
+        if self.remaining[c] == 0 {
+            self.uncover(c);
+        }
+        self.remaining[c] += 1;
+    }
+    fn cover(&mut self, c: usize) {
+        self.right[self.left[c]] = self.right[c];
+        self.left[self.right[c]] = self.left[c];
+        let mut i = self.down[c];
+        while i != c {
+            let mut j = self.right[i];
+            while j != i {
+                self.unlink_vertical(j);
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+    fn uncover(&mut self, c: usize) {
+        let mut i = self.up[c];
+        while i != c {
+            let mut j = self.left[i];
+            while j != i {
+                self.relink_vertical(j);
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        self.right[self.left[c]] = c;
+        self.left[self.right[c]] = c;
+    }
+    fn hide_row(&mut self, r: usize) {
+        self.unlink_vertical(r);
+        let mut j = self.right[r];
+        while j != r {
+            self.unlink_vertical(j);
+            j = self.right[j];
+        }
+    }
+    fn unhide_row(&mut self, r: usize) {
+        let mut j = self.left[r];
+        while j != r {
+            self.relink_vertical(j);
+            j = self.left[j];
+        }
+        self.relink_vertical(r);
+    }
+    fn unlink_vertical(&mut self, node: usize) {
+        self.down[self.up[node]] = self.down[node];
+        self.up[self.down[node]] = self.up[node];
+        self.size[self.col[node]] -= 1;
+    }
+    fn relink_vertical(&mut self, node: usize) {
+        self.down[self.up[node]] = node;
+        self.up[self.down[node]] = node;
+        self.size[self.col[node]] += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knuth_example() {
+        // columns A..G, the unique cover is rows 0, 3, 4
+        let mut dlx = Dlx::new(7, 0);
+        for row in [
+            &[2, 4, 5][..],
+            &[0, 3, 6],
+            &[1, 2, 5],
+            &[0, 3],
+            &[1, 6],
+            &[3, 4, 6],
+        ] {
+            dlx.add_row(row);
+        }
+        let mut solution = dlx.solve().expect("solution exists");
+        solution.sort();
+        assert_eq!(solution, vec![0, 3, 4]);
+        assert_eq!(dlx.count_solutions(), 1);
+        let mut deepest = 0;
+        let found = dlx.solve_with_progress(|partial| {
+            deepest = deepest.max(partial.len());
+            true
+        });
+        assert_eq!((found.is_some(), deepest), (true, 3));
+        assert_eq!(dlx.solve_with_progress(|partial| partial.is_empty()), None);
+    }
+    #[test]
+    fn secondary_and_multiplicity() {
+        // place two identical dominoes on a 1x5 strip, cells are secondary
+        let mut dlx = Dlx::with_multiplicities(&[2], 5);
+        for start in 0..4 {
+            dlx.add_row(&[0, 1 + start, 2 + start]);
+        }
+        // {0,1}+{2,3}, {0,1}+{3,4}, {1,2}+{3,4}
+        assert_eq!(dlx.count_solutions(), 3);
+        let mut strict = Dlx::with_multiplicities(&[3, 0], 5);
+        for start in 0..4 {
+            strict.add_row(&[0, 2 + start, 3 + start]);
+        }
+        strict.add_row(&[1]);
+        assert_eq!(strict.solve(), None);
+        let mut single = Dlx::with_multiplicities(&[1, 1], 2);
+        single.add_row(&[0, 2]);
+        single.add_row(&[0, 1, 3]);
+        single.add_row(&[1, 2]);
+        assert_eq!(single.solutions(5), vec![vec![1]]);
+    }
+}
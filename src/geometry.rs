@@ -0,0 +1,279 @@
+//! Axis-aligned (orthogonal) simple polygons on integer coordinates.
+use crate::prefix_sum::{CompressedGrid, breakpoints};
+use anyhow::{Result, anyhow, bail};
+use glam::{I64Vec2, IVec2};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Closed polygon region, boundary included. Vertices are stored in walk order
+/// without repeating the first one at the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrthoPolygon {
+    vertices: Vec<IVec2>,
+}
+
+impl OrthoPolygon {
+    /// Builds a polygon from vertices given in walk order and validates it.
+    pub fn new(vertices: Vec<IVec2>) -> Result<Self> {
+        let polygon = Self { vertices };
+        polygon.validate()?;
+        Ok(polygon)
+    }
+    /// Reconstructs the walk order from an unordered set of corner vertices.
+    /// Every corner has exactly one horizontal and one vertical neighbour, which
+    /// are found by pairing up sorted vertices on the same row and column.
+    pub fn from_unordered(vertices: &[IVec2]) -> Result<Self> {
+        let len = vertices.len();
+        if len < 4 {
+            bail!("polygon needs at least 4 vertices, got {len}");
+        }
+        let horizontal = pair_on_lines(vertices, |v| (v.y, v.x))?;
+        let vertical = pair_on_lines(vertices, |v| (v.x, v.y))?;
+        let start = (0..len)
+            .min_by_key(|&i| (vertices[i].x, vertices[i].y))
+            .expect("non empty");
+        let mut ordered = Vec::with_capacity(len);
+        let mut cur = start;
+        loop {
+            ordered.push(vertices[cur]);
+            let next_h = horizontal[cur];
+            ordered.push(vertices[next_h]);
+            cur = vertical[next_h];
+            if cur == start || ordered.len() >= len {
+                break;
+            }
+        }
+        if ordered.len() != len || cur != start {
+            bail!("vertices form more than one polygon");
+        }
+        Self::new(ordered)
+    }
+    pub fn vertices(&self) -> &[IVec2] {
+        &self.vertices
+    }
+    pub fn edges(&self) -> impl Iterator<Item = [IVec2; 2]> + '_ {
+        self.vertices
+            .iter()
+            .copied()
+            .circular_tuple_windows()
+            .map(|(a, b)| [a, b])
+    }
+    /// Twice the signed area, positive for counter-clockwise in a y-up frame.
+    fn signed_area2(&self) -> i64 {
+        self.edges()
+            .map(|[a, b]| a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64)
+            .sum()
+    }
+    /// Orientation in a y-up frame; on y-down screens (puzzle rows) it is mirrored.
+    pub fn orientation(&self) -> Orientation {
+        if self.signed_area2() > 0 {
+            Orientation::CounterClockwise
+        } else {
+            Orientation::Clockwise
+        }
+    }
+    /// Shoelace area of the region enclosed by the boundary.
+    pub fn area(&self) -> i64 {
+        self.signed_area2().abs() / 2
+    }
+    pub fn contains_point(&self, p: IVec2) -> bool {
+        self.contains_doubled(p.as_i64vec2() * 2)
+    }
+    /// Point test in doubled coordinates so that half-integer points can be probed.
+    fn contains_doubled(&self, q: I64Vec2) -> bool {
+        let mut crossings = 0;
+        for [a, b] in self.edges() {
+            let (a, b) = (a.as_i64vec2() * 2, b.as_i64vec2() * 2);
+            let (min, max) = (a.min(b), a.max(b));
+            if (min.x..=max.x).contains(&q.x) && (min.y..=max.y).contains(&q.y) {
+                return true;
+            }
+            if a.x == b.x && a.x > q.x && (min.y..max.y).contains(&q.y) {
+                crossings += 1;
+            }
+        }
+        crossings % 2 == 1
+    }
+    /// Whether the closed segment `a`-`b` lies within the region. Between consecutive
+    /// vertex coordinates the inside status cannot change, so probing the endpoints
+    /// and the midpoint of every such gap is enough.
+    fn contains_segment(&self, a: IVec2, b: IVec2) -> bool {
+        let axis = if a.y == b.y { 0 } else { 1 };
+        let (lo, hi) = (a[axis].min(b[axis]), a[axis].max(b[axis]));
+        let probe = |t: i64| {
+            let mut q = a.as_i64vec2() * 2;
+            q[axis] = t;
+            q
+        };
+        let stops: Vec<i64> = self
+            .vertices
+            .iter()
+            .map(|v| v[axis])
+            .filter(|t| lo < *t && *t < hi)
+            .chain([lo, hi])
+            .sorted_unstable()
+            .dedup()
+            .map(|t| t as i64)
+            .collect();
+        stops.iter().all(|&t| self.contains_doubled(probe(t * 2)))
+            && stops
+                .iter()
+                .tuple_windows()
+                .all(|(s, t)| self.contains_doubled(probe(s + t)))
+    }
+    /// Whether the closed axis-aligned rectangle with opposite corners `a` and `b`
+    /// lies within the region. The region is simply connected, so it is enough for
+    /// the rectangle outline to lie within it.
+    pub fn contains_rect(&self, a: IVec2, b: IVec2) -> bool {
+        let corners = [a, IVec2::new(b.x, a.y), b, IVec2::new(a.x, b.y)];
+        corners
+            .iter()
+            .circular_tuple_windows()
+            .all(|(c, d)| self.contains_segment(*c, *d))
+    }
+    /// Lattice rasterisation of the region (boundary included) over coordinates
+    /// compressed on the vertices, filled row by row with a parity scanline.
+    pub fn compressed_grid(&self) -> CompressedGrid {
+        let xs = breakpoints(self.vertices.iter().map(|v| v.x));
+        let ys = breakpoints(self.vertices.iter().map(|v| v.y));
+        let (cols, rows) = (xs.len() - 1, ys.len() - 1);
+        let edges: Vec<_> = self.edges().map(|[a, b]| (a.min(b), a.max(b))).collect();
+        let mut cells = vec![false; cols * rows];
+        for (r, &y) in ys[..rows].iter().enumerate() {
+            let row = &mut cells[r * cols..(r + 1) * cols];
+            let toggles: Vec<i32> = edges
+                .iter()
+                .filter(|(min, max)| min.x == max.x && (min.y..max.y).contains(&y))
+                .map(|(min, _)| min.x)
+                .sorted_unstable()
+                .collect();
+            let mut t = 0;
+            for (c, &x) in xs[..cols].iter().enumerate() {
+                while t < toggles.len() && toggles[t] < x {
+                    t += 1;
+                }
+                row[c] = t % 2 == 1;
+            }
+            for (min, max) in edges
+                .iter()
+                .filter(|(min, max)| (min.y..=max.y).contains(&y))
+            {
+                let from = xs.partition_point(|x| *x < min.x);
+                let to = xs.partition_point(|x| *x <= max.x);
+                row[from..to].iter_mut().for_each(|cell| *cell = true);
+            }
+        }
+        CompressedGrid::with_breakpoints(xs, ys, |c, r| cells[r * cols + c])
+    }
+    /// Checks that all edges are axis-aligned with non-zero length and that edges
+    /// only meet their neighbours, at the shared vertex.
+    pub fn validate(&self) -> Result<()> {
+        let len = self.vertices.len();
+        if len < 4 {
+            bail!("polygon needs at least 4 vertices, got {len}");
+        }
+        let edges: Vec<_> = self.edges().collect();
+        for (i, [a, b]) in edges.iter().enumerate() {
+            if a == b {
+                bail!("zero length edge at vertex {a}");
+            }
+            if a.x != b.x && a.y != b.y {
+                bail!("edge {a} -> {b} is not axis-aligned");
+            }
+            let next = edges[(i + 1) % len];
+            if (*b - *a).signum() == (next[0] - next[1]).signum() {
+                bail!("edge {a} -> {b} folds back onto the next edge");
+            }
+        }
+        for (i, j) in (0..len).tuple_combinations() {
+            if j == i + 1 || (i == 0 && j == len - 1) {
+                continue;
+            }
+            if segments_intersect(edges[i], edges[j]) {
+                bail!(
+                    "edges {} -> {} and {} -> {} intersect",
+                    edges[i][0],
+                    edges[i][1],
+                    edges[j][0],
+                    edges[j][1]
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn segments_intersect(e: [IVec2; 2], f: [IVec2; 2]) -> bool {
+    let (e_min, e_max) = (e[0].min(e[1]), e[0].max(e[1]));
+    let (f_min, f_max) = (f[0].min(f[1]), f[0].max(f[1]));
+    e_min.x <= f_max.x && f_min.x <= e_max.x && e_min.y <= f_max.y && f_min.y <= e_max.y
+}
+
+/// For every vertex, the index of its neighbour along the line selected by `key`
+/// (`(line, position)`), pairing sorted vertices on each line two by two.
+fn pair_on_lines(vertices: &[IVec2], key: impl Fn(&IVec2) -> (i32, i32)) -> Result<Vec<usize>> {
+    let mut lines: HashMap<i32, Vec<usize>> = HashMap::new();
+    for (i, v) in vertices.iter().enumerate() {
+        lines.entry(key(v).0).or_default().push(i);
+    }
+    let mut pairs = vec![usize::MAX; vertices.len()];
+    for (line, mut ids) in lines {
+        if ids.len() % 2 == 1 {
+            bail!("odd number of vertices on line {line}");
+        }
+        ids.sort_by_key(|&i| key(&vertices[i]).1);
+        for (&i, &j) in ids.iter().tuples() {
+            if vertices[i] == vertices[j] {
+                return Err(anyhow!("duplicate vertex {}", vertices[i]));
+            }
+            pairs[i] = j;
+            pairs[j] = i;
+        }
+    }
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn l_shape() -> Vec<IVec2> {
+        [(0, 0), (6, 0), (6, 2), (2, 2), (2, 6), (0, 6)]
+            .map(IVec2::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn reconstruct_and_measure() -> Result<()> {
+        let mut shuffled = l_shape();
+        shuffled.reverse();
+        shuffled.swap(1, 4);
+        let polygon = OrthoPolygon::from_unordered(&shuffled)?;
+        assert_eq!(polygon.vertices().len(), 6);
+        assert_eq!(polygon.area(), 20);
+        assert_eq!(polygon.orientation(), Orientation::CounterClockwise);
+        assert!(polygon.contains_point(IVec2::new(1, 5)));
+        assert!(polygon.contains_point(IVec2::new(6, 1)));
+        assert!(!polygon.contains_point(IVec2::new(3, 3)));
+        assert!(polygon.contains_rect(IVec2::new(0, 0), IVec2::new(6, 2)));
+        assert!(polygon.contains_rect(IVec2::new(2, 6), IVec2::new(0, 0)));
+        assert!(!polygon.contains_rect(IVec2::new(0, 0), IVec2::new(3, 3)));
+        Ok(())
+    }
+    #[test]
+    fn validation_errors() {
+        let diagonal = [(0, 0), (4, 0), (4, 4), (1, 3)].map(IVec2::from).to_vec();
+        assert!(OrthoPolygon::new(diagonal).is_err());
+        let bow_tie = [(0, 0), (4, 0), (4, 2), (2, 2), (2, -2), (0, -2)]
+            .map(IVec2::from)
+            .to_vec();
+        assert!(OrthoPolygon::new(bow_tie).is_err());
+        assert!(OrthoPolygon::new(l_shape()).is_ok());
+    }
+}
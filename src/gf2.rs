@@ -0,0 +1,214 @@
+//! Linear algebra over GF(2) with rows packed into `u64` words.
+
+use anyhow::{Result, bail};
+
+/// Largest nullspace [`BitMatrix::min_weight_solution`] is willing to walk.
+pub const MAX_NULLITY: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMatrix {
+    rows: usize,
+    cols: usize,
+    words: usize,
+    data: Vec<u64>,
+}
+
+impl BitMatrix {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words = cols.div_ceil(64).max(1);
+        Self {
+            rows,
+            cols,
+            words,
+            data: vec![0; rows * words],
+        }
+    }
+    pub fn from_rows(rows: &[Vec<bool>]) -> Self {
+        let cols = rows.first().map_or(0, Vec::len);
+        let mut matrix = Self::new(rows.len(), cols);
+        for (r, row) in rows.iter().enumerate() {
+            assert_eq!(row.len(), cols, "ragged rows");
+            for (c, bit) in row.iter().enumerate() {
+                matrix.set(r, c, *bit);
+            }
+        }
+        matrix
+    }
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        self.data[r * self.words + c / 64] >> (c % 64) & 1 == 1
+    }
+    pub fn set(&mut self, r: usize, c: usize, bit: bool) {
+        let word = &mut self.data[r * self.words + c / 64];
+        if bit {
+            *word |= 1 << (c % 64);
+        } else {
+            *word &= !(1 << (c % 64));
+        }
+    }
+    fn xor_rows(&mut self, target: usize, source: usize) {
+        for w in 0..self.words {
+            self.data[target * self.words + w] ^= self.data[source * self.words + w];
+        }
+    }
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for w in 0..self.words {
+            self.data.swap(a * self.words + w, b * self.words + w);
+        }
+    }
+    /// Reduces in place to reduced row echelon form, returns the pivot columns
+    /// of the first `cols` columns in row order.
+    fn eliminate(&mut self, cols: usize) -> Vec<usize> {
+        let mut pivots = vec![];
+        for c in 0..cols {
+            let r = pivots.len();
+            let Some(p) = (r..self.rows).find(|p| self.get(*p, c)) else {
+                continue;
+            };
+            self.swap_rows(r, p);
+            for other in 0..self.rows {
+                if other != r && self.get(other, c) {
+                    self.xor_rows(other, r);
+                }
+            }
+            pivots.push(c);
+        }
+        pivots
+    }
+    pub fn rank(&self) -> usize {
+        self.clone().eliminate(self.cols).len()
+    }
+    /// Copy of `self` with `b` appended as an extra column.
+    fn augmented(&self, b: &[bool]) -> Self {
+        assert_eq!(b.len(), self.rows, "right hand side length mismatch");
+        let mut aug = Self::new(self.rows, self.cols + 1);
+        for (r, rhs) in b.iter().enumerate() {
+            for c in 0..self.cols {
+                aug.set(r, c, self.get(r, c));
+            }
+            aug.set(r, self.cols, *rhs);
+        }
+        aug
+    }
+    /// Some `x` with `self * x = b`, free variables set to zero.
+    pub fn solve(&self, b: &[bool]) -> Option<Vec<bool>> {
+        let mut aug = self.augmented(b);
+        let pivots = aug.eliminate(self.cols);
+        if (pivots.len()..self.rows).any(|r| aug.get(r, self.cols)) {
+            return None;
+        }
+        let mut x = vec![false; self.cols];
+        for (r, c) in pivots.iter().enumerate() {
+            x[*c] = aug.get(r, self.cols);
+        }
+        Some(x)
+    }
+    /// Basis of `{x : self * x = 0}`, one vector per free column.
+    pub fn nullspace(&self) -> Vec<Vec<bool>> {
+        let mut reduced = self.clone();
+        let pivots = reduced.eliminate(self.cols);
+        (0..self.cols)
+            .filter(|c| !pivots.contains(c))
+            .map(|free| {
+                let mut x = vec![false; self.cols];
+                x[free] = true;
+                for (r, c) in pivots.iter().enumerate() {
+                    x[*c] = reduced.get(r, free);
+                }
+                x
+            })
+            .collect()
+    }
+    /// Solution of `self * x = b` with the fewest set bits. Walks all `2^nullity`
+    /// solutions in Gray code order, so it is meant for small nullspaces and
+    /// fails above [`MAX_NULLITY`]. `None` if there is no solution at all.
+    pub fn min_weight_solution(&self, b: &[bool]) -> Result<Option<Vec<bool>>> {
+        let pack = |v: &[bool]| {
+            let mut words = vec![0_u64; self.cols.div_ceil(64).max(1)];
+            for (i, bit) in v.iter().enumerate() {
+                words[i / 64] |= (*bit as u64) << (i % 64);
+            }
+            words
+        };
+        let weight = |words: &[u64]| words.iter().map(|w| w.count_ones()).sum::<u32>();
+        let Some(solution) = self.solve(b) else {
+            return Ok(None);
+        };
+        let mut cur = pack(&solution);
+        let basis: Vec<Vec<u64>> = self.nullspace().iter().map(|v| pack(v)).collect();
+        if basis.len() > MAX_NULLITY {
+            bail!(
+                "nullity {} is above {MAX_NULLITY}, too many solutions to walk",
+                basis.len()
+            );
+        }
+        let mut best = (weight(&cur), cur.clone());
+        for step in 1..1_u64 << basis.len() {
+            let flip = &basis[step.trailing_zeros() as usize];
+            cur.iter_mut().zip(flip).for_each(|(c, f)| *c ^= f);
+            let w = weight(&cur);
+            if w < best.0 {
+                best = (w, cur.clone());
+            }
+        }
+        Ok(Some(
+            (0..self.cols)
+                .map(|i| best.1[i / 64] >> (i % 64) & 1 == 1)
+                .collect(),
+        ))
+    }
+    /// `self * x` over GF(2).
+    pub fn mul_vec(&self, x: &[bool]) -> Vec<bool> {
+        (0..self.rows)
+            .map(|r| (0..self.cols).filter(|c| x[*c] && self.get(r, *c)).count() % 2 == 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(s: &str) -> Vec<bool> {
+        s.chars().map(|c| c == '1').collect()
+    }
+
+    #[test]
+    fn rank_nullspace_solve() {
+        let m = BitMatrix::from_rows(&[bits("1100"), bits("0110"), bits("1010")]);
+        assert_eq!(m.rank(), 2);
+        let null = m.nullspace();
+        assert_eq!(null.len(), 2);
+        for v in &null {
+            assert_eq!(m.mul_vec(v), bits("000"));
+        }
+        let x = m.solve(&bits("101")).expect("consistent");
+        assert_eq!(m.mul_vec(&x), bits("101"));
+        assert_eq!(m.solve(&bits("100")), None);
+    }
+    #[test]
+    fn min_weight() -> Result<()> {
+        // buttons as columns, lights as rows: first day10 example machine
+        let buttons = [&[3][..], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]];
+        let mut m = BitMatrix::new(4, buttons.len());
+        for (c, lights) in buttons.iter().enumerate() {
+            for l in lights.iter() {
+                m.set(*l, c, true);
+            }
+        }
+        let target = bits("0110");
+        let x = m.min_weight_solution(&target)?.expect("reachable");
+        assert_eq!(x.iter().filter(|b| **b).count(), 2);
+        assert_eq!(m.mul_vec(&x), target);
+        // every column free
+        let wide = BitMatrix::new(1, 64);
+        assert!(wide.min_weight_solution(&[false]).is_err());
+        assert_eq!(wide.min_weight_solution(&[true])?, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+//! Petgraph wrapper addressing nodes by interned string names.
+use anyhow::{Result, anyhow};
+use petgraph::Graph;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct NamedGraph<N = (), E = ()> {
+    graph: Graph<N, E>,
+    names: Vec<String>,
+    ids: HashMap<String, NodeIndex>,
+}
+
+impl<N, E> Default for NamedGraph<N, E> {
+    fn default() -> Self {
+        Self {
+            graph: Graph::new(),
+            names: vec![],
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<N, E> NamedGraph<N, E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds a node, or replaces the weight of the node already having this name.
+    pub fn add_node(&mut self, name: &str, weight: N) -> NodeIndex {
+        if let Some(&ni) = self.ids.get(name) {
+            self.graph[ni] = weight;
+            return ni;
+        }
+        let ni = self.graph.add_node(weight);
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), ni);
+        ni
+    }
+    /// Index of the named node, adding it with a default weight when missing.
+    pub fn intern(&mut self, name: &str) -> NodeIndex
+    where
+        N: Default,
+    {
+        match self.ids.get(name) {
+            Some(&ni) => ni,
+            None => self.add_node(name, N::default()),
+        }
+    }
+    pub fn add_edge(&mut self, source: &str, target: &str, weight: E) -> EdgeIndex
+    where
+        N: Default,
+    {
+        let (a, b) = (self.intern(source), self.intern(target));
+        self.graph.add_edge(a, b, weight)
+    }
+    pub fn get(&self, name: &str) -> Option<NodeIndex> {
+        self.ids.get(name).copied()
+    }
+    pub fn index(&self, name: &str) -> Result<NodeIndex> {
+        self.get(name).ok_or(anyhow!("node `{name}` not found"))
+    }
+    pub fn name(&self, ni: NodeIndex) -> &str {
+        &self.names[ni.index()]
+    }
+    pub fn graph(&self) -> &Graph<N, E> {
+        &self.graph
+    }
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interning() -> Result<()> {
+        let mut graph: NamedGraph = NamedGraph::new();
+        graph.add_edge("svr", "a", ());
+        graph.add_edge("a", "longer-name", ());
+        graph.add_edge("svr", "longer-name", ());
+        assert_eq!(graph.node_count(), 3);
+        let svr = graph.index("svr")?;
+        assert_eq!(graph.name(svr), "svr");
+        assert_eq!(graph.graph().neighbors(svr).count(), 2);
+        assert!(graph.index("out").is_err());
+        Ok(())
+    }
+}
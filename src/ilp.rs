@@ -0,0 +1,439 @@
+//! Integer linear programming over non-negative integer variables.
+//!
+//! Problems are described once as an [`IlpProblem`] and handed to any [`IlpSolver`].
+//! The native `lpsolve` backend is used when the `lpsolve` feature is enabled
+//! (the default), otherwise [`DefaultSolver`] is the pure Rust [`BranchAndBound`].
+use anyhow::{Result, anyhow, bail};
+
+const EPS: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sense {
+    Minimize,
+    Maximize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    Le,
+    Eq,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    pub coeffs: Vec<f64>,
+    pub relation: Relation,
+    pub rhs: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IlpProblem {
+    sense: Sense,
+    objective: Vec<f64>,
+    constraints: Vec<Constraint>,
+}
+
+impl IlpProblem {
+    pub fn minimize(objective: Vec<f64>) -> Self {
+        Self {
+            sense: Sense::Minimize,
+            objective,
+            constraints: vec![],
+        }
+    }
+    pub fn maximize(objective: Vec<f64>) -> Self {
+        Self {
+            sense: Sense::Maximize,
+            ..Self::minimize(objective)
+        }
+    }
+    pub fn constraint(mut self, coeffs: Vec<f64>, relation: Relation, rhs: f64) -> Self {
+        assert_eq!(coeffs.len(), self.vars(), "constraint width mismatch");
+        self.constraints.push(Constraint {
+            coeffs,
+            relation,
+            rhs,
+        });
+        self
+    }
+    pub fn le(self, coeffs: Vec<f64>, rhs: f64) -> Self {
+        self.constraint(coeffs, Relation::Le, rhs)
+    }
+    pub fn eq(self, coeffs: Vec<f64>, rhs: f64) -> Self {
+        self.constraint(coeffs, Relation::Eq, rhs)
+    }
+    pub fn ge(self, coeffs: Vec<f64>, rhs: f64) -> Self {
+        self.constraint(coeffs, Relation::Ge, rhs)
+    }
+    pub fn vars(&self) -> usize {
+        self.objective.len()
+    }
+    pub fn sense(&self) -> Sense {
+        self.sense
+    }
+    pub fn objective(&self) -> &[f64] {
+        &self.objective
+    }
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+    pub fn is_satisfied_by(&self, values: &[i64]) -> bool {
+        values.len() == self.vars()
+            && values.iter().all(|v| *v >= 0)
+            && self.constraints.iter().all(|c| {
+                let lhs: f64 = c
+                    .coeffs
+                    .iter()
+                    .zip(values)
+                    .map(|(a, v)| a * *v as f64)
+                    .sum();
+                match c.relation {
+                    Relation::Le => lhs <= c.rhs + EPS,
+                    Relation::Eq => (lhs - c.rhs).abs() <= EPS,
+                    Relation::Ge => lhs >= c.rhs - EPS,
+                }
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IlpSolution {
+    pub values: Vec<i64>,
+    pub objective: f64,
+}
+
+impl IlpSolution {
+    fn new(problem: &IlpProblem, values: Vec<i64>) -> Self {
+        let objective = problem
+            .objective
+            .iter()
+            .zip(&values)
+            .map(|(c, v)| c * *v as f64)
+            .sum();
+        Self { values, objective }
+    }
+}
+
+pub trait IlpSolver {
+    fn solve(&self, problem: &IlpProblem) -> Result<IlpSolution>;
+}
+
+#[cfg(feature = "lpsolve")]
+pub type DefaultSolver = LpSolve;
+#[cfg(not(feature = "lpsolve"))]
+pub type DefaultSolver = BranchAndBound;
+
+#[cfg(feature = "lpsolve")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LpSolve;
+
+#[cfg(feature = "lpsolve")]
+impl IlpSolver for LpSolve {
+    fn solve(&self, problem: &IlpProblem) -> Result<IlpSolution> {
+        let cols = problem.vars() as i32;
+        let mut builder = lpsolve::Problem::builder().cols(cols);
+        builder = match problem.sense {
+            Sense::Minimize => builder.min(&problem.objective),
+            Sense::Maximize => builder.max(&problem.objective),
+        };
+        builder = builder
+            .integer_vars(&(1..=cols).collect::<Vec<_>>())
+            .non_negative_integers()
+            .verbosity(lpsolve::Verbosity::Critical);
+        for c in &problem.constraints {
+            builder = match c.relation {
+                Relation::Le => builder.le(&c.coeffs, c.rhs),
+                Relation::Eq => builder.eq(&c.coeffs, c.rhs),
+                Relation::Ge => builder.ge(&c.coeffs, c.rhs),
+            };
+        }
+        let solution = builder.solve()?;
+        if !solution.is_optimal() {
+            bail!("lpsolve finished with status {:?}", solution.status());
+        }
+        let values = solution
+            .variables()
+            .ok_or(anyhow!("lpsolve returned no variables"))?
+            .iter()
+            .map(|v| v.round() as i64)
+            .collect();
+        Ok(IlpSolution::new(problem, values))
+    }
+}
+
+/// Depth-first branch and bound over LP relaxations solved with a dense
+/// two-phase simplex.
+#[derive(Debug, Clone, Copy)]
+pub struct BranchAndBound {
+    pub node_limit: usize,
+}
+
+impl Default for BranchAndBound {
+    fn default() -> Self {
+        Self {
+            node_limit: 1_000_000,
+        }
+    }
+}
+
+impl IlpSolver for BranchAndBound {
+    fn solve(&self, problem: &IlpProblem) -> Result<IlpSolution> {
+        let sign = match problem.sense {
+            Sense::Minimize => 1.,
+            Sense::Maximize => -1.,
+        };
+        let objective: Vec<f64> = problem.objective.iter().map(|c| c * sign).collect();
+        let integral_objective = objective.iter().all(|c| c.fract() == 0.);
+        let mut best: Option<(f64, Vec<i64>)> = None;
+        let mut stack: Vec<Vec<Constraint>> = vec![vec![]];
+        let mut nodes = 0;
+        while let Some(bounds) = stack.pop() {
+            nodes += 1;
+            if nodes > self.node_limit {
+                bail!("branch and bound node limit {} reached", self.node_limit);
+            }
+            let rows: Vec<&Constraint> = problem.constraints.iter().chain(&bounds).collect();
+            let (values, value) = match simplex(&objective, &rows) {
+                Lp::Optimal(values, value) => (values, value),
+                Lp::Infeasible => continue,
+                Lp::Unbounded => bail!("problem is unbounded"),
+            };
+            let bound = if integral_objective {
+                (value - 1e-6).ceil()
+            } else {
+                value
+            };
+            if best.as_ref().is_some_and(|(best, _)| bound >= best - 1e-6) {
+                continue;
+            }
+            let fractional = values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i, (v - v.round()).abs()))
+                .filter(|(_, frac)| *frac > 1e-6)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((var, _)) = fractional else {
+                best = Some((value, values.iter().map(|v| v.round() as i64).collect()));
+                continue;
+            };
+            let unit = |relation, rhs| {
+                let mut coeffs = vec![0.; problem.vars()];
+                coeffs[var] = 1.;
+                Constraint {
+                    coeffs,
+                    relation,
+                    rhs,
+                }
+            };
+            let mut down = bounds.clone();
+            down.push(unit(Relation::Le, values[var].floor()));
+            let mut up = bounds;
+            up.push(unit(Relation::Ge, values[var].ceil()));
+            // explore the side closer to the relaxation first
+            if values[var].fract() < 0.5 {
+                stack.extend([up, down]);
+            } else {
+                stack.extend([down, up]);
+            }
+        }
+        let (_, values) = best.ok_or(anyhow!("problem is infeasible"))?;
+        Ok(IlpSolution::new(problem, values))
+    }
+}
+
+enum Lp {
+    Optimal(Vec<f64>, f64),
+    Infeasible,
+    Unbounded,
+}
+
+/// Minimises `objective · x` subject to `rows` and `x >= 0`.
+fn simplex(objective: &[f64], rows: &[&Constraint]) -> Lp {
+    let n = objective.len();
+    let m = rows.len();
+    let slacks = rows.iter().filter(|r| r.relation != Relation::Eq).count();
+    let width = n + slacks + m;
+    let rhs = width;
+    let mut tableau = vec![vec![0.; width + 1]; m];
+    let mut basis = vec![0; m];
+    let mut artificial = vec![false; width];
+    let mut slack = n;
+    for (i, row) in rows.iter().enumerate() {
+        let flip = if row.rhs < 0. { -1. } else { 1. };
+        let relation = match (row.relation, flip < 0.) {
+            (Relation::Le, true) => Relation::Ge,
+            (Relation::Ge, true) => Relation::Le,
+            (relation, _) => relation,
+        };
+        for (j, a) in row.coeffs.iter().enumerate() {
+            tableau[i][j] = a * flip;
+        }
+        tableau[i][rhs] = row.rhs * flip;
+        match relation {
+            Relation::Le => {
+                tableau[i][slack] = 1.;
+                basis[i] = slack;
+            }
+            Relation::Ge => {
+                tableau[i][slack] = -1.;
+            }
+            Relation::Eq => {}
+        }
+        if relation != Relation::Eq {
+            slack += 1;
+        }
+        if relation != Relation::Le {
+            let a = n + slacks + i;
+            tableau[i][a] = 1.;
+            artificial[a] = true;
+            basis[i] = a;
+        }
+    }
+    // phase 1: drive the sum of artificial variables to zero
+    let mut cost = vec![0.; width + 1];
+    for i in (0..m).filter(|i| artificial[basis[*i]]) {
+        for (c, t) in cost.iter_mut().zip(&tableau[i]) {
+            *c -= t;
+        }
+    }
+    for (j, c) in cost.iter_mut().enumerate().take(width) {
+        if artificial[j] {
+            *c = 0.;
+        }
+    }
+    if !pivot_to_optimum(&mut tableau, &mut cost, &mut basis, &artificial, true) {
+        return Lp::Unbounded;
+    }
+    if -cost[rhs] > 1e-7 {
+        return Lp::Infeasible;
+    }
+    // move degenerate artificial variables out of the basis where possible
+    for i in 0..m {
+        if artificial[basis[i]]
+            && let Some(j) = (0..width).find(|j| !artificial[*j] && tableau[i][*j].abs() > EPS)
+        {
+            pivot(&mut tableau, &mut cost, &mut basis, i, j);
+        }
+    }
+    // phase 2: original objective expressed over the non-basic variables
+    let mut cost = vec![0.; width + 1];
+    cost[..n].copy_from_slice(objective);
+    for i in 0..m {
+        let cb = if basis[i] < n {
+            objective[basis[i]]
+        } else {
+            0.
+        };
+        if cb != 0. {
+            for (c, t) in cost.iter_mut().zip(&tableau[i]) {
+                *c -= cb * t;
+            }
+        }
+    }
+    if !pivot_to_optimum(&mut tableau, &mut cost, &mut basis, &artificial, false) {
+        return Lp::Unbounded;
+    }
+    let mut values = vec![0.; n];
+    for i in 0..m {
+        if basis[i] < n {
+            values[basis[i]] = tableau[i][rhs];
+        }
+    }
+    Lp::Optimal(values, -cost[rhs])
+}
+
+/// Runs simplex pivots with Bland's rule, returns `false` if unbounded.
+fn pivot_to_optimum(
+    tableau: &mut [Vec<f64>],
+    cost: &mut [f64],
+    basis: &mut [usize],
+    artificial: &[bool],
+    allow_artificial: bool,
+) -> bool {
+    let width = cost.len() - 1;
+    loop {
+        let Some(col) =
+            (0..width).find(|j| cost[*j] < -EPS && (allow_artificial || !artificial[*j]))
+        else {
+            return true;
+        };
+        let leaving = (0..tableau.len())
+            .filter(|i| tableau[*i][col] > EPS)
+            .min_by(|a, b| {
+                let ra = tableau[*a][width] / tableau[*a][col];
+                let rb = tableau[*b][width] / tableau[*b][col];
+                ra.total_cmp(&rb).then(basis[*a].cmp(&basis[*b]))
+            });
+        let Some(row) = leaving else {
+            return false;
+        };
+        pivot(tableau, cost, basis, row, col);
+    }
+}
+
+fn pivot(tableau: &mut [Vec<f64>], cost: &mut [f64], basis: &mut [usize], row: usize, col: usize) {
+    let p = tableau[row][col];
+    tableau[row].iter_mut().for_each(|t| *t /= p);
+    let pivot_row = tableau[row].clone();
+    for (i, r) in tableau.iter_mut().enumerate() {
+        let factor = r[col];
+        if i != row && factor != 0. {
+            r.iter_mut()
+                .zip(&pivot_row)
+                .for_each(|(t, p)| *t -= factor * p);
+        }
+    }
+    let factor = cost[col];
+    if factor != 0. {
+        cost.iter_mut()
+            .zip(&pivot_row)
+            .for_each(|(c, p)| *c -= factor * p);
+    }
+    basis[row] = col;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn knapsack() -> IlpProblem {
+        // max 5a + 4b + 3c, 2a + 3b + c <= 5, 4a + b + 2c <= 11, 3a + 4b + 2c <= 8
+        IlpProblem::maximize(vec![5., 4., 3.])
+            .le(vec![2., 3., 1.], 5.)
+            .le(vec![4., 1., 2.], 11.)
+            .le(vec![3., 4., 2.], 8.)
+    }
+    fn covering() -> IlpProblem {
+        // min a + b + c + d, day10 style equalities
+        IlpProblem::minimize(vec![1.; 4])
+            .eq(vec![1., 0., 1., 0.], 3.)
+            .eq(vec![0., 1., 1., 1.], 5.)
+            .ge(vec![1., 1., 0., 0.], 2.)
+    }
+
+    #[test]
+    fn branch_and_bound() -> Result<()> {
+        let solver = BranchAndBound::default();
+        let solution = solver.solve(&knapsack())?;
+        assert_eq!(solution.objective, 13.);
+        assert!(knapsack().is_satisfied_by(&solution.values));
+        let solution = solver.solve(&covering())?;
+        assert_eq!(solution.objective, 5.);
+        assert!(covering().is_satisfied_by(&solution.values));
+        let infeasible = IlpProblem::minimize(vec![1.]).eq(vec![2.], 3.);
+        assert!(solver.solve(&infeasible).is_err());
+        Ok(())
+    }
+    #[cfg(feature = "lpsolve")]
+    #[test]
+    fn backends_agree() -> Result<()> {
+        for problem in [knapsack(), covering()] {
+            let native = LpSolve.solve(&problem)?;
+            let pure = BranchAndBound::default().solve(&problem)?;
+            assert_eq!(native.objective, pure.objective);
+            assert!(problem.is_satisfied_by(&native.values));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+//! Static KD-tree over integer points with nearest-neighbour and radius queries.
+use glam::{I64Vec2, I64Vec3, IVec2};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+pub trait KdPoint: Copy {
+    const DIM: usize;
+    fn coord(&self, axis: usize) -> i64;
+
+    fn dist_sq(&self, other: &Self) -> i64 {
+        (0..Self::DIM)
+            .map(|axis| (self.coord(axis) - other.coord(axis)).pow(2))
+            .sum()
+    }
+}
+
+impl KdPoint for I64Vec3 {
+    const DIM: usize = 3;
+    fn coord(&self, axis: usize) -> i64 {
+        self[axis]
+    }
+}
+
+impl KdPoint for I64Vec2 {
+    const DIM: usize = 2;
+    fn coord(&self, axis: usize) -> i64 {
+        self[axis]
+    }
+}
+
+impl KdPoint for IVec2 {
+    const DIM: usize = 2;
+    fn coord(&self, axis: usize) -> i64 {
+        self[axis] as i64
+    }
+}
+
+/// Implicit tree: the split point of every subslice of `order` sits at its middle,
+/// split axis is `depth % DIM`.
+#[derive(Debug, Clone)]
+pub struct KdTree<P> {
+    points: Vec<P>,
+    order: Vec<usize>,
+}
+
+impl<P: KdPoint> KdTree<P> {
+    pub fn new(points: Vec<P>) -> Self {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        build(&points, &mut order, 0);
+        Self { points, order }
+    }
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+    pub fn points(&self) -> &[P] {
+        &self.points
+    }
+    /// `k` nearest points to `query` as `(index, squared distance)`,
+    /// ordered by distance then index.
+    pub fn nearest(&self, query: P, k: usize) -> Vec<(usize, i64)> {
+        if k == 0 {
+            return vec![];
+        }
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        self.nearest_rec(&self.order, 0, query, k, &mut heap);
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|(dist, i)| (i, dist))
+            .collect()
+    }
+    fn nearest_rec(
+        &self,
+        order: &[usize],
+        depth: usize,
+        query: P,
+        k: usize,
+        heap: &mut BinaryHeap<(i64, usize)>,
+    ) {
+        if order.is_empty() {
+            return;
+        }
+        let mid = order.len() / 2;
+        let i = order[mid];
+        let point = self.points[i];
+        heap.push((point.dist_sq(&query), i));
+        if heap.len() > k {
+            heap.pop();
+        }
+        let axis = depth % P::DIM;
+        let diff = query.coord(axis) - point.coord(axis);
+        let (near, far) = if diff < 0 {
+            (&order[..mid], &order[mid + 1..])
+        } else {
+            (&order[mid + 1..], &order[..mid])
+        };
+        self.nearest_rec(near, depth + 1, query, k, heap);
+        let worst = heap.peek().map(|(dist, _)| *dist).unwrap_or(i64::MAX);
+        if heap.len() < k || diff * diff <= worst {
+            self.nearest_rec(far, depth + 1, query, k, heap);
+        }
+    }
+    /// Indices of all points with squared distance to `query` at most `radius_sq`.
+    pub fn within_radius(&self, query: P, radius_sq: i64) -> Vec<usize> {
+        let mut result = vec![];
+        self.within_rec(&self.order, 0, query, radius_sq, &mut result);
+        result.sort_unstable();
+        result
+    }
+    fn within_rec(
+        &self,
+        order: &[usize],
+        depth: usize,
+        query: P,
+        radius_sq: i64,
+        result: &mut Vec<usize>,
+    ) {
+        if order.is_empty() {
+            return;
+        }
+        let mid = order.len() / 2;
+        let i = order[mid];
+        let point = self.points[i];
+        if point.dist_sq(&query) <= radius_sq {
+            result.push(i);
+        }
+        let axis = depth % P::DIM;
+        let diff = query.coord(axis) - point.coord(axis);
+        if diff <= 0 || diff * diff <= radius_sq {
+            self.within_rec(&order[..mid], depth + 1, query, radius_sq, result);
+        }
+        if diff >= 0 || diff * diff <= radius_sq {
+            self.within_rec(&order[mid + 1..], depth + 1, query, radius_sq, result);
+        }
+    }
+    /// All pairs `[i, j]` with `i < j` and squared distance at most `radius_sq`,
+    /// ordered by distance then indices.
+    pub fn pairs_within(&self, radius_sq: i64) -> Vec<([usize; 2], i64)> {
+        let mut pairs: Vec<_> = (0..self.points.len())
+            .flat_map(|i| {
+                self.within_radius(self.points[i], radius_sq)
+                    .into_iter()
+                    .filter(move |&j| j > i)
+                    .map(move |j| ([i, j], self.points[i].dist_sq(&self.points[j])))
+            })
+            .collect();
+        pairs.sort_unstable_by_key(|&([i, j], dist)| (dist, i, j));
+        pairs
+    }
+    /// Lazily yields every pair `[i, j]` with `i < j` ordered by distance then indices,
+    /// keeping only a growing neighbour buffer per point instead of all n² pairs.
+    pub fn closest_pairs(self) -> ClosestPairs<P> {
+        let len = self.points.len();
+        let mut pairs = ClosestPairs {
+            tree: self,
+            neighbours: vec![vec![]; len],
+            fetched: vec![0; len],
+            heap: BinaryHeap::with_capacity(len),
+        };
+        for i in 0..len {
+            pairs.push_next(i);
+        }
+        pairs
+    }
+}
+
+fn build<P: KdPoint>(points: &[P], order: &mut [usize], depth: usize) {
+    if order.len() <= 1 {
+        return;
+    }
+    let mid = order.len() / 2;
+    let axis = depth % P::DIM;
+    order.select_nth_unstable_by_key(mid, |&i| points[i].coord(axis));
+    let (left, right) = order.split_at_mut(mid);
+    build(points, left, depth + 1);
+    build(points, &mut right[1..], depth + 1);
+}
+
+#[derive(Debug, Clone)]
+pub struct ClosestPairs<P> {
+    tree: KdTree<P>,
+    // pending neighbours with greater index, stored farthest first
+    neighbours: Vec<Vec<(usize, i64)>>,
+    fetched: Vec<usize>,
+    heap: BinaryHeap<Reverse<(i64, usize, usize)>>,
+}
+
+impl<P: KdPoint> ClosestPairs<P> {
+    fn push_next(&mut self, i: usize) {
+        let len = self.tree.len();
+        while self.neighbours[i].is_empty() && self.fetched[i] < len {
+            let k = (self.fetched[i] * 2).max(8).min(len);
+            let fetched = self.fetched[i];
+            self.neighbours[i] = self
+                .tree
+                .nearest(self.tree.points[i], k)
+                .into_iter()
+                .skip(fetched)
+                .filter(|&(j, _)| j > i)
+                .rev()
+                .collect();
+            self.fetched[i] = k;
+        }
+        if let Some((j, dist)) = self.neighbours[i].pop() {
+            self.heap.push(Reverse((dist, i, j)));
+        }
+    }
+}
+
+impl<P: KdPoint> Iterator for ClosestPairs<P> {
+    type Item = [usize; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((_, i, j)) = self.heap.pop()?;
+        self.push_next(i);
+        Some([i, j])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    fn points() -> Vec<I64Vec3> {
+        (0..60_i64)
+            .map(|i| I64Vec3::new(i * 37 % 23, i * 11 % 17, i * 7 % 13))
+            .collect()
+    }
+
+    #[test]
+    fn nearest_and_radius() {
+        let points = points();
+        let tree = KdTree::new(points.clone());
+        let query = I64Vec3::new(5, 5, 5);
+        let brute: Vec<_> = (0..points.len())
+            .map(|i| (i, points[i].dist_sq(&query)))
+            .sorted_by_key(|&(i, dist)| (dist, i))
+            .collect();
+        assert_eq!(tree.nearest(query, 7), brute[..7]);
+        let inside: Vec<_> = brute
+            .iter()
+            .filter(|(_, dist)| *dist <= 30)
+            .map(|(i, _)| *i)
+            .sorted()
+            .collect();
+        assert_eq!(tree.within_radius(query, 30), inside);
+    }
+    #[test]
+    fn closest_pairs_match_brute_force() {
+        let points = points();
+        let brute: Vec<_> = (0..points.len())
+            .tuple_combinations()
+            .map(|(i, j)| ([i, j], points[i].dist_sq(&points[j])))
+            .sorted_by_key(|&([i, j], dist)| (dist, i, j))
+            .collect();
+        let tree = KdTree::new(points);
+        let within = tree.pairs_within(20);
+        let expected: Vec<_> = brute.iter().filter(|(_, d)| *d <= 20).copied().collect();
+        assert_eq!(within, expected);
+        let lazy: Vec<_> = tree.closest_pairs().collect();
+        assert_eq!(
+            lazy,
+            brute.iter().map(|(pair, _)| *pair).collect::<Vec<_>>()
+        );
+    }
+    #[test]
+    fn closest_pairs_of_few_points() {
+        for n in [0, 1, 2, 7] {
+            let points: Vec<_> = points().into_iter().take(n).collect();
+            let brute: Vec<_> = (0..n)
+                .tuple_combinations()
+                .sorted_by_key(|&(i, j)| (points[i].dist_sq(&points[j]), i, j))
+                .map(|(i, j)| [i, j])
+                .collect();
+            let lazy: Vec<_> = KdTree::new(points).closest_pairs().collect();
+            assert_eq!(lazy, brute, "{n} points");
+        }
+    }
+}
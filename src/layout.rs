@@ -0,0 +1,118 @@
+//! Fixed-width text blocks split into column groups, worksheet style.
+//!
+//! A column that is blank on every line separates two groups. Ragged lines are
+//! padded with spaces, so alignment inside a group is kept as written.
+use std::ops::Range;
+
+#[derive(Debug, Clone)]
+pub struct Layout {
+    lines: Vec<Vec<char>>,
+    groups: Vec<Range<usize>>,
+}
+
+impl Layout {
+    pub fn new(text: &str) -> Self {
+        let width = text
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Vec<char>> = text
+            .lines()
+            .map(|line| {
+                let mut chars: Vec<char> = line.chars().collect();
+                chars.resize(width, ' ');
+                chars
+            })
+            .collect();
+        let blank = |c: usize| lines.iter().all(|line| line[c] == ' ');
+        let mut groups = vec![];
+        let mut start = None;
+        for c in 0..=width {
+            match (start, c == width || blank(c)) {
+                (None, false) => start = Some(c),
+                (Some(s), true) => {
+                    groups.push(s..c);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        Self { lines, groups }
+    }
+    pub fn height(&self) -> usize {
+        self.lines.len()
+    }
+    pub fn width(&self) -> usize {
+        self.lines.first().map_or(0, Vec::len)
+    }
+    /// Column groups from left to right, reverse the iterator to read right to left.
+    pub fn groups(&self) -> impl DoubleEndedIterator<Item = Group<'_>> + ExactSizeIterator {
+        self.groups.iter().map(|columns| Group {
+            layout: self,
+            columns: columns.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Group<'a> {
+    layout: &'a Layout,
+    columns: Range<usize>,
+}
+
+impl Group<'_> {
+    /// Column span of the group in the original text.
+    pub fn columns(&self) -> Range<usize> {
+        self.columns.clone()
+    }
+    /// Text of every line inside the group, top to bottom, padding included.
+    pub fn rows(&self) -> Vec<String> {
+        self.layout
+            .lines
+            .iter()
+            .map(|line| line[self.columns.clone()].iter().collect())
+            .collect()
+    }
+    /// Text of every column inside the group left to right, each read top to
+    /// bottom over the lines in `rows` only.
+    pub fn columns_in(&self, rows: Range<usize>) -> Vec<String> {
+        self.columns
+            .clone()
+            .map(|c| {
+                self.layout.lines[rows.clone()]
+                    .iter()
+                    .map(|line| line[c])
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_keep_alignment() {
+        let layout = Layout::new("12 7 \n 3 45\n+  * ");
+        assert_eq!((layout.height(), layout.width()), (3, 5));
+        let groups: Vec<_> = layout.groups().collect();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].columns(), 0..2);
+        assert_eq!(groups[0].rows(), vec!["12", " 3", "+ "]);
+        assert_eq!(groups[0].columns_in(0..2), vec!["1 ", "23"]);
+        assert_eq!(groups[1].rows(), vec!["7 ", "45", "* "]);
+        let right_to_left: Vec<_> = layout.groups().rev().map(|g| g.columns()).collect();
+        assert_eq!(right_to_left, vec![3..5, 0..2]);
+    }
+    #[test]
+    fn ragged_lines_are_padded() {
+        let layout = Layout::new("ab  c\nd\n");
+        assert_eq!(layout.width(), 5);
+        let groups: Vec<_> = layout.groups().collect();
+        assert_eq!(groups[0].rows(), vec!["ab", "d "]);
+        assert_eq!(groups[1].columns_in(0..2), vec!["c "]);
+        assert_eq!(Layout::new("").groups().len(), 0);
+    }
+}
@@ -0,0 +1,20 @@
+pub mod animate;
+pub mod automaton;
+pub mod cli;
+pub mod dag;
+pub mod dial;
+pub mod dlx;
+pub mod geometry;
+pub mod gf2;
+pub mod graph;
+pub mod ilp;
+pub mod kdtree;
+pub mod layout;
+pub mod numeric;
+pub mod parsers;
+pub mod polyomino;
+pub mod prefix_sum;
+pub mod render;
+pub mod repdigits;
+pub mod search;
+pub mod trace;
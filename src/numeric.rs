@@ -0,0 +1,78 @@
+//! Numeric policy for answers that can outgrow machine integers.
+//!
+//! Accumulators use [`Num`], a `u128` whose overflow is reported as an error,
+//! or an unbounded `num::BigUint` with the `bigint` feature.
+use anyhow::{Result, anyhow};
+use num::{BigUint, One, Zero};
+
+#[cfg(not(feature = "bigint"))]
+pub type Num = u128;
+#[cfg(feature = "bigint")]
+pub type Num = BigUint;
+
+/// Addition and multiplication that fail instead of wrapping.
+pub trait CheckedArith: Sized + Zero + One {
+    fn try_add(&self, rhs: &Self) -> Result<Self>;
+    fn try_mul(&self, rhs: &Self) -> Result<Self>;
+}
+
+macro_rules! checked_arith {
+    ($($t:ty),*) => {$(
+        impl CheckedArith for $t {
+            fn try_add(&self, rhs: &Self) -> Result<Self> {
+                self.checked_add(*rhs)
+                    .ok_or(anyhow!("{self} + {rhs} overflows {}", stringify!($t)))
+            }
+            fn try_mul(&self, rhs: &Self) -> Result<Self> {
+                self.checked_mul(*rhs)
+                    .ok_or(anyhow!("{self} * {rhs} overflows {}", stringify!($t)))
+            }
+        }
+    )*};
+}
+
+checked_arith!(u32, u64, u128, usize, i64, i128);
+
+impl CheckedArith for BigUint {
+    fn try_add(&self, rhs: &Self) -> Result<Self> {
+        Ok(self + rhs)
+    }
+    fn try_mul(&self, rhs: &Self) -> Result<Self> {
+        Ok(self * rhs)
+    }
+}
+
+pub fn try_sum<T: CheckedArith>(items: impl IntoIterator<Item = T>) -> Result<T> {
+    items
+        .into_iter()
+        .try_fold(T::zero(), |acc, item| acc.try_add(&item))
+}
+
+pub fn try_product<T: CheckedArith>(items: impl IntoIterator<Item = T>) -> Result<T> {
+    items
+        .into_iter()
+        .try_fold(T::one(), |acc, item| acc.try_mul(&item))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflow_is_an_error() -> Result<()> {
+        assert_eq!(try_sum([1_u64, 2, 3])?, 6);
+        assert_eq!(try_product([2_u32, 3, 7])?, 42);
+        let err = try_sum([u64::MAX, 1]).unwrap_err();
+        assert_eq!(err.to_string(), "18446744073709551615 + 1 overflows u64");
+        assert!(try_product([1_u128 << 64, 1 << 64]).is_err());
+        assert_eq!(try_sum(Vec::<i64>::new())?, 0);
+        Ok(())
+    }
+    #[test]
+    fn big_uint_never_overflows() -> Result<()> {
+        let big = try_product([1_u128 << 64, 1 << 64].map(BigUint::from))?;
+        assert_eq!(big, BigUint::one() << 128);
+        assert_eq!(try_sum([Num::one(), Num::one()])?, Num::from(2_u32));
+        Ok(())
+    }
+}
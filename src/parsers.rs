@@ -0,0 +1,208 @@
+//! nom combinators for the recurring puzzle input shapes.
+//!
+//! Everything works on `&str` with nom's default error type, [`run`] parses a
+//! whole input and turns failures into an error pointing at line and column.
+use anyhow::{Result, anyhow};
+use glam::{I64Vec3, IVec2};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while1};
+use nom::character::complete::{alphanumeric1, char, digit1, line_ending, none_of, space0, space1};
+use nom::combinator::{all_consuming, map_opt, map_res, opt, recognize};
+use nom::error::ErrorKind;
+use nom::multi::{many1, separated_list0, separated_list1};
+use nom::sequence::{delimited, pair, separated_pair, terminated};
+use nom::{Finish, Parser};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+pub type Error<'a> = nom::error::Error<&'a str>;
+pub type IResult<'a, O> = nom::IResult<&'a str, O>;
+
+/// Runs `parser` over the complete `input`, unparsed leftovers are an error.
+pub fn run<'a, O>(
+    parser: impl Parser<&'a str, Output = O, Error = Error<'a>>,
+    input: &'a str,
+) -> Result<O> {
+    all_consuming(parser)
+        .parse_complete(input)
+        .finish()
+        .map(|(_, output)| output)
+        .map_err(|e| {
+            // leftovers start at the line break before the line that failed to parse
+            let rest = match e.code {
+                ErrorKind::Eof => e.input.trim_start_matches(['\r', '\n']),
+                _ => e.input,
+            };
+            let offset = input.len() - rest.len();
+            let line = input[..offset].matches('\n').count() + 1;
+            let column = offset - input[..offset].rfind('\n').map_or(0, |i| i + 1) + 1;
+            let near = rest.lines().next().unwrap_or_default();
+            anyhow!(
+                "parse error at line {line}, column {column} ({:?}): near {near:?}",
+                e.code
+            )
+        })
+}
+
+/// Unsigned decimal number.
+pub fn unsigned<T: FromStr>(input: &str) -> IResult<'_, T> {
+    map_res(digit1, str::parse).parse(input)
+}
+
+/// Decimal number with an optional leading sign.
+pub fn signed<T: FromStr>(input: &str) -> IResult<'_, T> {
+    map_res(
+        recognize(pair(opt(alt((char('-'), char('+')))), digit1)),
+        str::parse,
+    )
+    .parse(input)
+}
+
+/// One `item` per line.
+pub fn lines<'a, O>(
+    item: impl Parser<&'a str, Output = O, Error = Error<'a>>,
+) -> impl Parser<&'a str, Output = Vec<O>, Error = Error<'a>> {
+    separated_list1(line_ending, item)
+}
+
+/// `item,item,...`
+pub fn comma_separated<'a, O>(
+    item: impl Parser<&'a str, Output = O, Error = Error<'a>>,
+) -> impl Parser<&'a str, Output = Vec<O>, Error = Error<'a>> {
+    separated_list1(char(','), item)
+}
+
+/// Whitespace separated `item`s, padding at either end of the line allowed.
+pub fn spaced<'a, O>(
+    item: impl Parser<&'a str, Output = O, Error = Error<'a>>,
+) -> impl Parser<&'a str, Output = Vec<O>, Error = Error<'a>> {
+    delimited(space0, separated_list1(space1, item), space0)
+}
+
+/// Single `item` with optional spaces around it.
+pub fn padded<'a, O>(
+    item: impl Parser<&'a str, Output = O, Error = Error<'a>>,
+) -> impl Parser<&'a str, Output = O, Error = Error<'a>> {
+    delimited(space0, item, space0)
+}
+
+/// Comma separated `item`s between `open` and `close`, as in `(1,3)` or `{3,5,4}`.
+pub fn bracketed<'a, O>(
+    open: char,
+    item: impl Parser<&'a str, Output = O, Error = Error<'a>>,
+    close: char,
+) -> impl Parser<&'a str, Output = Vec<O>, Error = Error<'a>> {
+    delimited(char(open), comma_separated(item), char(close))
+}
+
+/// Inclusive `a-b` range of unsigned numbers.
+pub fn range<T: FromStr>(input: &str) -> IResult<'_, RangeInclusive<T>> {
+    separated_pair(unsigned, char('-'), unsigned)
+        .map(|(start, end)| start..=end)
+        .parse(input)
+}
+
+/// Unsigned number in base `radix`, letters for digits above 9 in either case.
+pub fn unsigned_radix<'a>(radix: u32) -> impl Parser<&'a str, Output = u64, Error = Error<'a>> {
+    map_res(
+        take_while1(move |c: char| c.is_digit(radix)),
+        move |digits| u64::from_str_radix(digits, radix),
+    )
+}
+
+/// `start-end` in base `radix`.
+pub fn range_radix<'a>(
+    radix: u32,
+) -> impl Parser<&'a str, Output = RangeInclusive<u64>, Error = Error<'a>> {
+    separated_pair(unsigned_radix(radix), char('-'), unsigned_radix(radix))
+        .map(|(start, end)| start..=end)
+}
+
+/// `x,y`
+pub fn ivec2(input: &str) -> IResult<'_, IVec2> {
+    separated_pair(signed, char(','), signed)
+        .map(|(x, y)| IVec2::new(x, y))
+        .parse(input)
+}
+
+/// `x,y,z`
+pub fn i64vec3(input: &str) -> IResult<'_, I64Vec3> {
+    (signed, char(','), signed, char(','), signed)
+        .map(|(x, _, y, _, z)| I64Vec3::new(x, y, z))
+        .parse(input)
+}
+
+/// An empty line between two blocks, consumes both line endings.
+pub fn blank_line(input: &str) -> IResult<'_, ()> {
+    (line_ending, line_ending).map(|_| ()).parse(input)
+}
+
+/// Blocks separated by blank lines.
+pub fn sections<'a, O>(
+    section: impl Parser<&'a str, Output = O, Error = Error<'a>>,
+) -> impl Parser<&'a str, Output = Vec<O>, Error = Error<'a>> {
+    separated_list1(blank_line, section)
+}
+
+/// Rectangular block of characters, each mapped through `cell`. A character
+/// `cell` rejects fails the parse at that position.
+pub fn grid<'a, T>(
+    cell: impl Fn(char) -> Option<T>,
+) -> impl Parser<&'a str, Output = Vec<Vec<T>>, Error = Error<'a>> {
+    lines(many1(map_opt(none_of("\r\n"), cell)))
+}
+
+/// `name: a b c` adjacency line, the target list may be empty.
+pub fn adjacency(input: &str) -> IResult<'_, (&str, Vec<&str>)> {
+    separated_pair(
+        alphanumeric1,
+        terminated(tag(":"), space0),
+        separated_list0(space1, alphanumeric1),
+    )
+    .parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes() -> Result<()> {
+        assert_eq!(
+            run(comma_separated(range::<u64>), "11-22,95-115")?,
+            vec![11..=22, 95..=115]
+        );
+        assert_eq!(
+            run(lines(ivec2), "7,1\n-2,3")?,
+            vec![IVec2::new(7, 1), IVec2::new(-2, 3)]
+        );
+        assert_eq!(run(range_radix(16), "0fF-1a0")?, 0xff..=0x1a0);
+        assert!(run(range_radix(16), "ff-1g").is_err());
+        assert_eq!(run(i64vec3, "162,817,812")?, I64Vec3::new(162, 817, 812));
+        assert_eq!(
+            run(bracketed('{', unsigned::<usize>, '}'), "{3,5,4,7}")?,
+            vec![3, 5, 4, 7]
+        );
+        assert_eq!(
+            run(spaced(unsigned::<u64>), " 45 64  387 23 ")?,
+            vec![45, 64, 387, 23]
+        );
+        assert_eq!(run(adjacency, "you: bbb ccc")?, ("you", vec!["bbb", "ccc"]));
+        let grid = run(grid(|c| (c != 'x').then_some(c == '#')), "#.\n.#")?;
+        assert_eq!(grid, vec![vec![true, false], vec![false, true]]);
+        let blocks = run(sections(lines(unsigned::<u32>)), "1\n2\n\n3")?;
+        assert_eq!(blocks, vec![vec![1, 2], vec![3]]);
+        Ok(())
+    }
+    #[test]
+    fn error_position() {
+        let err = run(lines(ivec2), "1,2\n3,4\n5;6").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("parse error at line 3, column 1"),
+            "{err}"
+        );
+        let err = run(grid(|c| (c != 'x').then_some(c)), "ab\nax").unwrap_err();
+        assert!(err.to_string().contains("line 2, column 2"), "{err}");
+    }
+}
@@ -0,0 +1,151 @@
+//! Polyominoes of any size with the eight orientations of the square's symmetry group.
+use anyhow::{Error, Result, anyhow};
+use glam::IVec2;
+use itertools::Itertools;
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Set of cells (`x` column, `y` row) translated so that both minima are zero,
+/// kept sorted so equal shapes compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Polyomino {
+    cells: Vec<IVec2>,
+}
+
+impl Polyomino {
+    pub fn new(cells: impl IntoIterator<Item = IVec2>) -> Self {
+        let cells: Vec<IVec2> = cells.into_iter().collect();
+        let min = cells.iter().copied().reduce(IVec2::min).unwrap_or_default();
+        let cells = cells
+            .into_iter()
+            .map(|c| c - min)
+            .sorted_by_key(|c| (c.y, c.x))
+            .dedup()
+            .collect();
+        Self { cells }
+    }
+    /// Shape from rows of filled flags, as in the `#`/`.` puzzle blocks.
+    pub fn from_grid(rows: &[Vec<bool>]) -> Self {
+        Self::new(rows.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, filled)| **filled)
+                .map(move |(x, _)| IVec2::new(x as i32, y as i32))
+        }))
+    }
+    pub fn cells(&self) -> &[IVec2] {
+        &self.cells
+    }
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+    pub fn width(&self) -> usize {
+        self.cells.iter().map(|c| c.x + 1).max().unwrap_or(0) as usize
+    }
+    pub fn height(&self) -> usize {
+        self.cells.iter().map(|c| c.y + 1).max().unwrap_or(0) as usize
+    }
+    pub fn rot_left(&self) -> Self {
+        Self::new(self.cells.iter().map(|c| IVec2::new(c.y, -c.x)))
+    }
+    pub fn flip(&self) -> Self {
+        Self::new(self.cells.iter().map(|c| IVec2::new(-c.x, c.y)))
+    }
+    /// All distinct orientations (rotations and their mirror images), sorted.
+    pub fn orientations(&self) -> Vec<Self> {
+        let rotations: Vec<Self> =
+            std::iter::successors(Some(self.clone()), |s| Some(s.rot_left()))
+                .take(4)
+                .collect();
+        rotations
+            .iter()
+            .map(Self::flip)
+            .chain(rotations.iter().cloned())
+            .sorted()
+            .dedup()
+            .collect()
+    }
+    /// Representative shared by all orientations of the same free polyomino.
+    pub fn canonical(&self) -> Self {
+        self.orientations().swap_remove(0)
+    }
+}
+
+impl Ord for Polyomino {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |c: &IVec2| (c.y, c.x);
+        self.cells.iter().map(key).cmp(other.cells.iter().map(key))
+    }
+}
+
+impl PartialOrd for Polyomino {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Polyomino {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let rows = s
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| match c {
+                        '#' => Ok(true),
+                        '.' => Ok(false),
+                        _ => Err(anyhow!("unexpected shape char {c:?}")),
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::from_grid(&rows))
+    }
+}
+
+impl Display for Polyomino {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height() as i32 {
+            for x in 0..self.width() as i32 {
+                let filled = self.cells.binary_search_by_key(&(y, x), |c| (c.y, c.x));
+                write!(f, "{}", if filled.is_ok() { "#" } else { "." })?;
+            }
+            writeln!(f)?;
+        }
+        std::fmt::Result::Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orientation_counts() -> Result<()> {
+        let square: Polyomino = "##\n##".parse()?;
+        assert_eq!(square.orientations().len(), 1);
+        let t: Polyomino = "###\n.#.".parse()?;
+        assert_eq!(t.orientations().len(), 4);
+        let domino: Polyomino = "##".parse()?;
+        assert_eq!(domino.orientations().len(), 2);
+        let s: Polyomino = ".##\n##.".parse()?;
+        assert_eq!(s.orientations().len(), 4);
+        let l: Polyomino = "#.\n#.\n##".parse()?;
+        assert_eq!(l.orientations().len(), 8);
+        let f: Polyomino = ".##\n##.\n.#.".parse()?;
+        assert_eq!(f.orientations().len(), 8);
+        Ok(())
+    }
+    #[test]
+    fn canonical_and_display() -> Result<()> {
+        let l: Polyomino = "#.\n#.\n##".parse()?;
+        let j: Polyomino = ".#\n.#\n##".parse()?;
+        assert_eq!(l.canonical(), j.canonical());
+        assert_eq!(l.flip(), j);
+        assert_eq!(l.rot_left().to_string(), "..#\n###\n");
+        assert_eq!((l.width(), l.height(), l.area()), (2, 3, 4));
+        Ok(())
+    }
+}
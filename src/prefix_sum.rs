@@ -0,0 +1,116 @@
+//! Coordinate-compressed 2D prefix sums over lattice cells.
+use glam::{IVec2, UVec2};
+use itertools::Itertools;
+
+/// Compressed breakpoints for the given key coordinates: every key `k` starts a
+/// one-wide slab `[k, k + 1)` and the gap up to the next key becomes a single slab.
+pub fn breakpoints(keys: impl IntoIterator<Item = i32>) -> Vec<i32> {
+    keys.into_iter()
+        .flat_map(|k| [k, k + 1])
+        .sorted_unstable()
+        .dedup()
+        .collect()
+}
+
+/// Filled/empty lattice region over compressed cells. Cell `(c, r)` stands for all
+/// integer points in `[xs[c], xs[c + 1]) x [ys[r], ys[r + 1])`.
+#[derive(Debug, Clone)]
+pub struct CompressedGrid {
+    xs: Vec<i32>,
+    ys: Vec<i32>,
+    // (rows + 1) x (cols + 1) inclusive prefix counts of filled cells
+    sums: Vec<u32>,
+}
+
+impl CompressedGrid {
+    /// Compresses on `keys` and asks `filled` once per cell with its lowest corner.
+    pub fn new(keys: &[IVec2], mut filled: impl FnMut(IVec2) -> bool) -> Self {
+        let xs = breakpoints(keys.iter().map(|k| k.x));
+        let ys = breakpoints(keys.iter().map(|k| k.y));
+        let (xs_c, ys_c) = (xs.clone(), ys.clone());
+        Self::with_breakpoints(xs, ys, |c, r| filled(IVec2::new(xs_c[c], ys_c[r])))
+    }
+    /// Builds from explicit breakpoints, `filled` is called with `(column, row)`.
+    pub fn with_breakpoints(
+        xs: Vec<i32>,
+        ys: Vec<i32>,
+        mut filled: impl FnMut(usize, usize) -> bool,
+    ) -> Self {
+        let cols = xs.len().saturating_sub(1);
+        let rows = ys.len().saturating_sub(1);
+        let mut sums = vec![0; (rows + 1) * (cols + 1)];
+        for r in 0..rows {
+            for c in 0..cols {
+                let i = (r + 1) * (cols + 1) + c + 1;
+                sums[i] =
+                    filled(c, r) as u32 + sums[i - 1] + sums[i - cols - 1] - sums[i - cols - 2];
+            }
+        }
+        Self { xs, ys, sums }
+    }
+    pub fn cols(&self) -> usize {
+        self.xs.len().saturating_sub(1)
+    }
+    pub fn rows(&self) -> usize {
+        self.ys.len().saturating_sub(1)
+    }
+    /// Compressed cell holding the lattice point `p`, if it is covered by the grid.
+    pub fn cell_index(&self, p: IVec2) -> Option<UVec2> {
+        let find = |bps: &[i32], v: i32| {
+            let i = bps.partition_point(|b| *b <= v);
+            (i > 0 && i < bps.len()).then(|| i as u32 - 1)
+        };
+        Some(UVec2::new(find(&self.xs, p.x)?, find(&self.ys, p.y)?))
+    }
+    /// Filled cells in the inclusive block spanned by cells `a` and `b`, in O(1).
+    pub fn count_filled(&self, a: UVec2, b: UVec2) -> u32 {
+        let (lo, hi) = (a.min(b).as_usizevec2(), (a.max(b) + 1).as_usizevec2());
+        let w = self.cols() + 1;
+        self.sums[hi.y * w + hi.x] + self.sums[lo.y * w + lo.x]
+            - self.sums[lo.y * w + hi.x]
+            - self.sums[hi.y * w + lo.x]
+    }
+    /// Whether every cell of the inclusive block spanned by cells `a` and `b` is filled.
+    pub fn is_filled(&self, a: UVec2, b: UVec2) -> bool {
+        let size = (a.max(b) - a.min(b) + 1).as_usizevec2();
+        self.count_filled(a, b) as usize == size.x * size.y
+    }
+    /// Whether all lattice points of the rectangle with opposite corners `a` and `b`
+    /// are filled; points outside the grid count as empty.
+    pub fn contains_rect(&self, a: IVec2, b: IVec2) -> bool {
+        match (self.cell_index(a), self.cell_index(b)) {
+            (Some(a), Some(b)) => self.is_filled(a, b),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakpoints_split_gaps() {
+        assert_eq!(breakpoints([5, 1, 3, 4]), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(breakpoints([10, 2]), vec![2, 3, 10, 11]);
+    }
+    #[test]
+    fn rect_queries_match_lattice() {
+        // ring of width 1 around an empty hole
+        let filled = |p: IVec2| {
+            (0..=8).contains(&p.x)
+                && (0..=6).contains(&p.y)
+                && !((2..=6).contains(&p.x) && (2..=4).contains(&p.y))
+        };
+        let keys = [(0, 0), (8, 6), (2, 2), (6, 4), (1, 1), (7, 5)].map(IVec2::from);
+        let grid = CompressedGrid::new(&keys, filled);
+        for (a, b) in keys.iter().tuple_combinations() {
+            let (lo, hi) = (a.min(*b), a.max(*b));
+            let brute = (lo.x..=hi.x)
+                .cartesian_product(lo.y..=hi.y)
+                .all(|(x, y)| filled(IVec2::new(x, y)));
+            assert_eq!(grid.contains_rect(*a, *b), brute, "{a} {b}");
+        }
+        assert!(!grid.contains_rect(IVec2::new(-1, 0), IVec2::new(1, 1)));
+    }
+}
@@ -0,0 +1,233 @@
+//! Pictures of puzzle states, saved as PNG or SVG depending on the file extension.
+//!
+//! A [`Scene`] is drawn in world units, one unit per grid cell, and scaled so
+//! that small grids get several pixels per cell and huge coordinates still fit.
+use crate::cli;
+use anyhow::{Context, Result, bail};
+use glam::{DVec2, IVec2};
+use std::fmt::Write;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+pub type Rgb = [u8; 3];
+
+pub const BACKGROUND: Rgb = [16, 16, 24];
+pub const GREY: Rgb = [110, 110, 110];
+pub const HIGHLIGHT: Rgb = [255, 215, 0];
+
+const MAX_PIXELS: f64 = 1200.0;
+const MAX_CELL_PIXELS: f64 = 8.0;
+
+#[derive(Debug, Clone)]
+enum Shape {
+    Rect {
+        min: IVec2,
+        size: IVec2,
+        colour: Rgb,
+    },
+    /// Closed outline through the centres of the given cells.
+    Outline { points: Vec<IVec2>, colour: Rgb },
+}
+
+#[derive(Debug, Clone)]
+pub struct Scene {
+    size: IVec2,
+    shapes: Vec<Shape>,
+}
+
+impl Scene {
+    /// Empty scene covering cells `0..width` by `0..height`.
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            size: IVec2::new(width, height).max(IVec2::ONE),
+            shapes: vec![],
+        }
+    }
+    pub fn cell(&mut self, p: IVec2, colour: Rgb) {
+        self.rect(p, IVec2::ONE, colour);
+    }
+    pub fn rect(&mut self, min: IVec2, size: IVec2, colour: Rgb) {
+        self.shapes.push(Shape::Rect { min, size, colour });
+    }
+    pub fn outline(&mut self, points: impl IntoIterator<Item = IVec2>, colour: Rgb) {
+        let points = points.into_iter().collect();
+        self.shapes.push(Shape::Outline { points, colour });
+    }
+    /// Pixels per world unit.
+    fn scale(&self) -> f64 {
+        (MAX_PIXELS / self.size.max_element() as f64).min(MAX_CELL_PIXELS)
+    }
+    fn pixel_size(&self) -> (u32, u32) {
+        let px = (self.size.as_dvec2() * self.scale()).ceil().max(DVec2::ONE);
+        (px.x as u32, px.y as u32)
+    }
+    pub fn to_svg(&self) -> String {
+        let (w, h) = self.pixel_size();
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {} {}\">\n",
+            self.size.x, self.size.y
+        );
+        let hex = |c: &Rgb| format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2]);
+        let _ = writeln!(
+            svg,
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+            hex(&BACKGROUND)
+        );
+        for shape in &self.shapes {
+            let _ = match shape {
+                Shape::Rect { min, size, colour } => writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    min.x,
+                    min.y,
+                    size.x,
+                    size.y,
+                    hex(colour)
+                ),
+                Shape::Outline { points, colour } => {
+                    let points: Vec<String> = points
+                        .iter()
+                        .map(|p| format!("{},{}", p.x as f64 + 0.5, p.y as f64 + 0.5))
+                        .collect();
+                    writeln!(
+                        svg,
+                        "<polygon points=\"{}\" fill=\"none\" stroke=\"{}\" \
+                         stroke-width=\"2\" vector-effect=\"non-scaling-stroke\"/>",
+                        points.join(" "),
+                        hex(colour)
+                    )
+                }
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+    /// Rasterised scene as `(width, height, rgb bytes)`.
+    pub fn to_rgb(&self) -> (u32, u32, Vec<u8>) {
+        let (w, h) = self.pixel_size();
+        let scale = self.scale();
+        let mut pixels = BACKGROUND.repeat((w * h) as usize);
+        let mut put = |x: i64, y: i64, colour: &Rgb| {
+            if (0..w as i64).contains(&x) && (0..h as i64).contains(&y) {
+                let i = (y as usize * w as usize + x as usize) * 3;
+                pixels[i..i + 3].copy_from_slice(colour);
+            }
+        };
+        for shape in &self.shapes {
+            match shape {
+                Shape::Rect { min, size, colour } => {
+                    // at least one pixel, even when cells are smaller than that
+                    let lo = (min.as_dvec2() * scale).floor();
+                    let hi = ((*min + *size).as_dvec2() * scale).ceil().max(lo + 1.0);
+                    for y in lo.y as i64..hi.y as i64 {
+                        for x in lo.x as i64..hi.x as i64 {
+                            put(x, y, colour);
+                        }
+                    }
+                }
+                Shape::Outline { points, colour } => {
+                    let centre = |p: &IVec2| ((p.as_dvec2() + 0.5) * scale).floor();
+                    for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+                        let (a, b) = (centre(a), centre(b));
+                        let steps = (b - a).abs().max_element().max(1.0);
+                        for k in 0..=steps as i64 {
+                            let p = a + (b - a) * (k as f64 / steps);
+                            put(p.x as i64, p.y as i64, colour);
+                        }
+                    }
+                }
+            }
+        }
+        (w, h, pixels)
+    }
+    /// Writes a `.png` or `.svg` file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let context = || format!("writing {}", path.display());
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("svg") => std::fs::write(path, self.to_svg()).with_context(context),
+            Some("png") => {
+                let (w, h, pixels) = self.to_rgb();
+                let file = BufWriter::new(File::create(path).with_context(context)?);
+                let mut encoder = png::Encoder::new(file, w, h);
+                encoder.set_color(png::ColorType::Rgb);
+                encoder.set_depth(png::BitDepth::Eight);
+                let mut writer = encoder.write_header().with_context(context)?;
+                writer.write_image_data(&pixels).with_context(context)
+            }
+            _ => bail!(
+                "unsupported image format for {}, use .png or .svg",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Distinct colour for the `i`-th item, hues spread by the golden angle.
+pub fn palette(i: usize) -> Rgb {
+    let hue = (i as f64 * 137.508) % 360.0;
+    hsv(hue, 0.65, 0.95)
+}
+
+/// Colour for an intensity in `0..=1`, dark blue through red to yellow.
+pub fn heat(t: f64) -> Rgb {
+    let t = t.clamp(0.0, 1.0);
+    hsv(240.0 + 180.0 * t, 0.9, 0.35 + 0.65 * t)
+}
+
+fn hsv(hue: f64, s: f64, v: f64) -> Rgb {
+    let h = (hue % 360.0) / 60.0;
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    [r, g, b].map(|channel| ((channel + m) * 255.0).round() as u8)
+}
+
+/// Output path given with `--render <path>` on the command line, if any.
+pub fn render_target() -> Result<Option<PathBuf>> {
+    cli::value(&cli::args(), "--render")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_shapes() {
+        let mut scene = Scene::new(4, 2);
+        scene.cell(IVec2::new(1, 0), [255, 0, 0]);
+        scene.outline([IVec2::ZERO, IVec2::new(3, 0), IVec2::new(3, 1)], HIGHLIGHT);
+        let svg = scene.to_svg();
+        assert!(svg.contains("width=\"32\" height=\"16\" viewBox=\"0 0 4 2\""));
+        assert!(svg.contains("<rect x=\"1\" y=\"0\" width=\"1\" height=\"1\" fill=\"#ff0000\"/>"));
+        assert!(svg.contains("points=\"0.5,0.5 3.5,0.5 3.5,1.5\""));
+        assert_eq!(palette(0), [242, 85, 85]);
+        assert_ne!(palette(1), palette(2));
+    }
+    #[test]
+    fn png_round_trip() -> Result<()> {
+        let mut scene = Scene::new(3000, 10);
+        scene.rect(IVec2::new(1500, 0), IVec2::new(1, 10), [0, 255, 0]);
+        let (w, h, pixels) = scene.to_rgb();
+        // 0.4 pixels per unit, thin rects still get a pixel column
+        assert_eq!((w, h), (1200, 4));
+        assert_eq!(pixels[600 * 3..600 * 3 + 3], [0, 255, 0]);
+        let path = std::env::temp_dir().join(format!("render-test-{}.png", std::process::id()));
+        scene.save(&path)?;
+        let decoder = png::Decoder::new(std::io::BufReader::new(File::open(&path)?));
+        let info = decoder.read_info()?.info().clone();
+        std::fs::remove_file(&path)?;
+        assert_eq!((info.width, info.height), (1200, 4));
+        assert!(scene.save(Path::new("picture.gif")).is_err());
+        Ok(())
+    }
+}
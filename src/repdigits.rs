@@ -0,0 +1,582 @@
+//! Sums of numbers whose digits are one block repeated, without visiting every
+//! number in the range.
+//!
+//! In base `b`, an `len` digit number built from a `period` digit block is
+//! `block * R` with the repunit-like multiplier `R = (b^len - 1) / (b^period - 1)`,
+//! so the ones inside a range are a run of consecutive blocks. Numbers with
+//! several periods are counted once by summing over primitive periods, which
+//! come out of the per-period sums by Möbius inversion.
+
+use anyhow::{Result, bail};
+use std::ops::{Range, RangeInclusive};
+use std::str::FromStr;
+
+/// How ids are written: the base and, for ids padded with leading zeros, the
+/// number of digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Digits {
+    radix: u32,
+    width: Option<u32>,
+}
+
+impl Digits {
+    pub const DECIMAL: Self = Self {
+        radix: 10,
+        width: None,
+    };
+
+    pub fn new(radix: u32, width: Option<u32>) -> Result<Self> {
+        if !(2..=36).contains(&radix) {
+            bail!("radix must be within 2..=36, got {radix}");
+        }
+        let digits = Self { radix, width };
+        if let Some(width) = width
+            && !(1..=digits.max_len()).contains(&width)
+        {
+            bail!("width must be within 1..={}, got {width}", digits.max_len());
+        }
+        Ok(digits)
+    }
+    pub fn radix(&self) -> u32 {
+        self.radix
+    }
+    pub fn width(&self) -> Option<u32> {
+        self.width
+    }
+    /// `n` written out, padded to the width if there is one.
+    pub fn format(&self, mut n: u64) -> String {
+        let mut digits = vec![];
+        while n > 0 || digits.is_empty() {
+            let digit = (n % self.radix as u64) as u32;
+            digits.push(char::from_digit(digit, self.radix).expect("digit below radix"));
+            n /= self.radix as u64;
+        }
+        let width = self.width.unwrap_or(0) as usize;
+        digits.resize(digits.len().max(width), '0');
+        digits.iter().rev().collect()
+    }
+    /// Largest id that can be written, every `u64` without a width.
+    pub fn max_id(&self) -> u64 {
+        match self.width {
+            Some(width) if width < self.max_len() => (self.pow(width) - 1) as u64,
+            _ => u64::MAX,
+        }
+    }
+    /// Whether `id` written out is one block repeated `times` times, ids wider
+    /// than the width never are.
+    pub fn is_repeated(&self, id: u64, times: u32) -> bool {
+        let id = self.format(id);
+        let (len, times) = (id.len(), times as usize);
+        self.width.is_none_or(|width| len == width as usize)
+            && times > 0
+            && len.is_multiple_of(times)
+            && id[..len / times].repeat(times) == id
+    }
+    /// Digits of the largest `u64`.
+    fn max_len(&self) -> u32 {
+        u64::MAX.ilog(self.radix as u64) + 1
+    }
+    fn lengths(&self) -> RangeInclusive<u32> {
+        match self.width {
+            Some(width) => width..=width,
+            None => 1..=self.max_len(),
+        }
+    }
+    fn pow(&self, exp: u32) -> u128 {
+        (self.radix as u128).pow(exp)
+    }
+}
+
+/// Which ids count as invalid. Repeat counts are those of the shortest block,
+/// except that an id repeated `k` times also counts for every divisor of `k`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Policy {
+    /// A block repeated exactly `k` times, the block may itself repeat:
+    /// `1111` is `11` twice.
+    Exactly(u32),
+    /// A block repeated `k` times or more.
+    AtLeast(u32),
+    /// A block repeated at least twice and at most `k` times.
+    AtMost(u32),
+    /// A block repeated at least twice, with a length within the bounds.
+    BlockLen(RangeInclusive<u32>),
+    /// Ids reading the same backwards.
+    Palindrome,
+}
+
+impl Policy {
+    /// Whether `len` digit ids with shortest block `period` match.
+    fn allows(&self, len: u32, period: u32) -> bool {
+        let repeats = len / period;
+        match self {
+            Policy::Exactly(k) => repeats.is_multiple_of(*k),
+            Policy::AtLeast(k) => repeats >= *k,
+            Policy::AtMost(k) => divisors(repeats).any(|r| (2..=*k).contains(&r)),
+            Policy::BlockLen(lens) => divisors(len)
+                .any(|block| block < len && block.is_multiple_of(period) && lens.contains(&block)),
+            Policy::Palindrome => unreachable!("palindromes have no blocks"),
+        }
+    }
+    /// Whether `id` matches, checked on its digits. [`matching`] finds all the
+    /// ids of a range without visiting them.
+    pub fn matches(&self, id: u64, digits: Digits) -> bool {
+        let written = digits.format(id);
+        let len = written.len() as u32;
+        if digits.width.is_some_and(|width| len != width) {
+            return false;
+        }
+        if *self == Policy::Palindrome {
+            return written.chars().rev().eq(written.chars());
+        }
+        let period = divisors(len)
+            .find(|period| digits.is_repeated(id, len / period))
+            .expect("every id repeats itself once");
+        self.allows(len, period)
+    }
+}
+
+impl FromStr for Policy {
+    type Err = anyhow::Error;
+
+    /// `exactly:2`, `at-least:2`, `at-most:3`, `block:2-4` or `palindrome`.
+    fn from_str(s: &str) -> Result<Self> {
+        let number = |n: &str| -> Result<u32> {
+            match n.parse() {
+                Ok(0) | Err(_) => bail!("bad repeat count or block length {n}"),
+                Ok(n) => Ok(n),
+            }
+        };
+        Ok(match s.split_once(':') {
+            None if s == "palindrome" => Policy::Palindrome,
+            Some(("exactly", k)) => Policy::Exactly(number(k)?),
+            Some(("at-least", k)) => Policy::AtLeast(number(k)?),
+            Some(("at-most", k)) => Policy::AtMost(number(k)?),
+            Some(("block", lens)) => {
+                let (min, max) = lens.split_once('-').unwrap_or((lens, lens));
+                Policy::BlockLen(number(min)?..=number(max)?)
+            }
+            _ => bail!("unknown policy {s}"),
+        })
+    }
+}
+
+/// Ids matching a policy, listed only when asked for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Matches {
+    pub sum: u128,
+    pub count: u128,
+    pub ids: Option<Vec<u64>>,
+}
+
+impl Matches {
+    pub fn merge(&mut self, other: Matches) {
+        self.sum += other.sum;
+        self.count += other.count;
+        if let (Some(ids), Some(more)) = (&mut self.ids, other.ids) {
+            ids.extend(more);
+        }
+    }
+}
+
+/// Ids in `[lo, hi]` matching `policy`, in increasing order if `list` is set.
+/// Sums and counts are computed without visiting the ids.
+pub fn matching(lo: u64, hi: u64, policy: &Policy, digits: Digits, list: bool) -> Matches {
+    if let Policy::Exactly(k) | Policy::AtLeast(k) = policy {
+        assert!(*k > 0, "a block is repeated at least once");
+    }
+    let mut matches = Matches {
+        ids: list.then(Vec::new),
+        ..Matches::default()
+    };
+    for len in digits.lengths() {
+        if *policy == Policy::Palindrome {
+            matches.merge(palindromes(lo, hi, len, digits, list));
+            continue;
+        }
+        for period in divisors(len).filter(|period| policy.allows(len, *period)) {
+            let (count, sum) = primitive_period_blocks(lo, hi, len, period, digits);
+            matches.count += count as u128;
+            matches.sum += sum as u128;
+            if let Some(ids) = &mut matches.ids {
+                ids.extend(primitive_ids(lo, hi, len, period, digits));
+            }
+        }
+    }
+    if let Some(ids) = &mut matches.ids {
+        ids.sort_unstable();
+    }
+    matches
+}
+
+/// Sum of numbers in `[lo, hi]` made of a block repeated exactly `times` times.
+/// The block itself may be periodic, `1111` is `11` twice.
+pub fn sum_repeated_exactly(lo: u64, hi: u64, times: u32, digits: Digits) -> u128 {
+    matching(lo, hi, &Policy::Exactly(times), digits, false).sum
+}
+
+/// Sum of numbers in `[lo, hi]` made of a block repeated `times` times or more.
+pub fn sum_repeated_at_least(lo: u64, hi: u64, times: u32, digits: Digits) -> u128 {
+    matching(lo, hi, &Policy::AtLeast(times), digits, false).sum
+}
+
+/// Blocks making `len` digit numbers in `[lo, hi]` with `period` as a period,
+/// with their repunit-like multiplier.
+fn period_blocks(
+    lo: u64,
+    hi: u64,
+    len: u32,
+    period: u32,
+    digits: Digits,
+) -> (RangeInclusive<u128>, u128) {
+    let multiplier = (digits.pow(len) - 1) / (digits.pow(period) - 1);
+    // padded ids may start with zeros, others may not unless they are zero
+    let smallest = match digits.width {
+        Some(_) => 0,
+        None if len == 1 => 0,
+        None => digits.pow(period - 1),
+    };
+    let first = smallest.max((lo as u128).div_ceil(multiplier));
+    let last = (digits.pow(period) - 1).min(hi as u128 / multiplier);
+    (first..=last, multiplier)
+}
+
+/// Count and sum of `len` digit numbers in `[lo, hi]` with `period` as a period.
+fn period_count_sum(lo: u64, hi: u64, len: u32, period: u32, digits: Digits) -> (u128, u128) {
+    let (blocks, multiplier) = period_blocks(lo, hi, len, period, digits);
+    let (first, last) = (*blocks.start(), *blocks.end());
+    if first > last {
+        return (0, 0);
+    }
+    let count = last - first + 1;
+    (count, multiplier * (first + last) * count / 2)
+}
+
+/// Count and sum of `len` digit numbers in `[lo, hi]` whose shortest period is
+/// `period`.
+fn primitive_period_blocks(
+    lo: u64,
+    hi: u64,
+    len: u32,
+    period: u32,
+    digits: Digits,
+) -> (i128, i128) {
+    divisors(period)
+        .map(|sub| {
+            let mu = mobius(period / sub) as i128;
+            let (count, sum) = period_count_sum(lo, hi, len, sub, digits);
+            (mu * count as i128, mu * sum as i128)
+        })
+        .fold((0, 0), |(count, sum), (c, s)| (count + c, sum + s))
+}
+
+/// The numbers counted by [`primitive_period_blocks`].
+fn primitive_ids(
+    lo: u64,
+    hi: u64,
+    len: u32,
+    period: u32,
+    digits: Digits,
+) -> impl Iterator<Item = u64> {
+    let (blocks, multiplier) = period_blocks(lo, hi, len, period, digits);
+    blocks
+        .filter(move |block| {
+            // a block that repeats itself gives a shorter period
+            divisors(period).filter(|sub| *sub < period).all(|sub| {
+                *block % digits.pow(sub) * ((digits.pow(period) - 1) / (digits.pow(sub) - 1))
+                    != *block
+            })
+        })
+        .map(move |block| (block * multiplier) as u64)
+}
+
+/// Palindromes of `len` digits in `[lo, hi]`. They grow with their first half,
+/// so the halves in range are consecutive and the sum comes from digit sums.
+fn palindromes(lo: u64, hi: u64, len: u32, digits: Digits, list: bool) -> Matches {
+    let half_len = len.div_ceil(2);
+    // digits mirrored into the low half, the middle one of odd lengths is not
+    let mirrored = len - half_len;
+    let skip = len % 2;
+    let reverse = |half: u128| -> u128 {
+        (0..mirrored)
+            .map(|i| digit(half, i + skip, digits) * digits.pow(mirrored - 1 - i))
+            .sum()
+    };
+    let palindrome = |half: u128| half * digits.pow(mirrored) + reverse(half);
+    let smallest = match digits.width {
+        Some(_) => 0,
+        None if len == 1 => 0,
+        None => digits.pow(half_len - 1),
+    };
+    let halves = smallest..digits.pow(half_len);
+    let first = partition_point(halves.clone(), |half| palindrome(half) < lo as u128);
+    let end = partition_point(halves, |half| palindrome(half) <= hi as u128);
+    if first >= end {
+        return Matches {
+            ids: list.then(Vec::new),
+            ..Matches::default()
+        };
+    }
+    let count = end - first;
+    let high = digits.pow(mirrored) * (first + end - 1) * count / 2;
+    let low: u128 = (0..mirrored)
+        .map(|i| {
+            let place = digits.pow(mirrored - 1 - i);
+            let position = i + skip;
+            place
+                * (digit_sum_below(end, position, digits)
+                    - digit_sum_below(first, position, digits))
+        })
+        .sum();
+    Matches {
+        sum: high + low,
+        count,
+        ids: list.then(|| (first..end).map(|half| palindrome(half) as u64).collect()),
+    }
+}
+
+fn digit(n: u128, position: u32, digits: Digits) -> u128 {
+    n / digits.pow(position) % digits.radix as u128
+}
+
+/// Sum of the digits at `position` of all numbers below `n`.
+fn digit_sum_below(n: u128, position: u32, digits: Digits) -> u128 {
+    let radix = digits.radix as u128;
+    let unit = digits.pow(position);
+    let cycle = unit * radix;
+    let full = n / cycle * unit * (radix * (radix - 1) / 2);
+    let rest = n % cycle;
+    let partial: u128 = (1..radix)
+        .map(|d| d * rest.saturating_sub(d * unit).min(unit))
+        .sum();
+    full + partial
+}
+
+/// First value in `range` for which `before` is false, `before` must be
+/// true for a prefix of the range only.
+fn partition_point(range: Range<u128>, before: impl Fn(u128) -> bool) -> u128 {
+    let (mut lo, mut hi) = (range.start, range.end);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if before(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+fn divisors(n: u32) -> impl Iterator<Item = u32> {
+    (1..=n).filter(move |d| n.is_multiple_of(*d))
+}
+
+fn mobius(mut n: u32) -> i32 {
+    let mut sign = 1;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0;
+            }
+            sign = -sign;
+        }
+        p += 1;
+    }
+    if n > 1 { -sign } else { sign }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECIMAL: Digits = Digits::DECIMAL;
+
+    #[test]
+    fn matches_brute_force() {
+        for (lo, hi) in [
+            (1, 1),
+            (1, 20_000),
+            (95, 115),
+            (998, 1012),
+            (123_123, 124_000),
+        ] {
+            for times in 1..=5_u32 {
+                let exactly: u128 = (lo..=hi)
+                    .filter(|n| DECIMAL.is_repeated(*n, times))
+                    .map(u128::from)
+                    .sum();
+                assert_eq!(sum_repeated_exactly(lo, hi, times, DECIMAL), exactly);
+                let at_least: u128 = (lo..=hi)
+                    .filter(|n| (times..=20).any(|t| DECIMAL.is_repeated(*n, t)))
+                    .map(u128::from)
+                    .sum();
+                assert_eq!(sum_repeated_at_least(lo, hi, times, DECIMAL), at_least);
+            }
+        }
+    }
+    #[test]
+    fn full_u64_range() {
+        assert_eq!(mobius(1), 1);
+        assert_eq!(mobius(6), 1);
+        assert_eq!(mobius(12), 0);
+        assert_eq!(mobius(19), -1);
+        // every number repeats itself once
+        let all = u64::MAX as u128 * (u64::MAX as u128 + 1) / 2;
+        assert_eq!(sum_repeated_at_least(1, u64::MAX, 1, DECIMAL), all);
+        assert_eq!(
+            sum_repeated_exactly(1, u64::MAX, 20, DECIMAL),
+            11_111_111_111_111_111_111
+        );
+        assert!(
+            sum_repeated_at_least(1, u64::MAX, 2, DECIMAL)
+                > sum_repeated_exactly(1, u64::MAX, 2, DECIMAL)
+        );
+        let binary = Digits::new(2, None).unwrap();
+        assert_eq!(sum_repeated_at_least(1, u64::MAX, 1, binary), all);
+    }
+    #[test]
+    fn other_bases_and_widths() -> Result<()> {
+        assert_eq!(Digits::new(16, None)?.format(0xab0), "ab0");
+        assert_eq!(Digits::new(36, Some(4))?.format(35), "000z");
+        assert!(Digits::new(37, None).is_err());
+        assert!(Digits::new(16, Some(17)).is_err());
+        assert_eq!(Digits::new(16, Some(4))?.max_id(), 0xffff);
+        assert_eq!(Digits::new(16, Some(16))?.max_id(), u64::MAX);
+        for digits in [
+            Digits::new(2, None)?,
+            Digits::new(16, None)?,
+            Digits::new(36, None)?,
+            Digits::new(16, Some(4))?,
+            Digits::new(10, Some(6))?,
+        ] {
+            for (lo, hi) in [(0, 3000), (0xfff0, 0x1_0040), (999_900, 1_000_100)] {
+                for times in 1..=4_u32 {
+                    let exactly: u128 = (lo..=hi)
+                        .filter(|n| digits.is_repeated(*n, times))
+                        .map(u128::from)
+                        .sum();
+                    let found = sum_repeated_exactly(lo, hi, times, digits);
+                    assert_eq!(found, exactly, "{digits:?} {lo} {hi} {times}");
+                    let at_least: u128 = (lo..=hi)
+                        .filter(|n| (times..=64).any(|t| digits.is_repeated(*n, t)))
+                        .map(u128::from)
+                        .sum();
+                    let found = sum_repeated_at_least(lo, hi, times, digits);
+                    assert_eq!(found, at_least, "{digits:?} {lo} {hi} {times}");
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Every way to write the id as a block repeated some number of times, as
+    /// `(block length, times)`. Ids that do not fit the width have none.
+    fn block_splits(n: u64, digits: Digits) -> Vec<(usize, usize)> {
+        let s = digits.format(n);
+        if digits.width.is_some_and(|width| s.len() != width as usize) {
+            return vec![];
+        }
+        (1..=s.len())
+            .filter(|block| s.len().is_multiple_of(*block))
+            .filter(|block| s[..*block].repeat(s.len() / block) == s)
+            .map(|block| (block, s.len() / block))
+            .collect()
+    }
+    fn brute_force(n: u64, policy: &Policy, digits: Digits) -> bool {
+        let splits = block_splits(n, digits);
+        let mut repeated = splits.iter().filter(|(_, times)| *times >= 2);
+        match policy {
+            Policy::Exactly(k) => splits.iter().any(|(_, times)| *times == *k as usize),
+            Policy::AtLeast(k) => splits.iter().any(|(_, times)| *times >= *k as usize),
+            Policy::AtMost(k) => repeated.any(|(_, times)| *times <= *k as usize),
+            Policy::BlockLen(lens) => repeated.any(|(block, _)| lens.contains(&(*block as u32))),
+            Policy::Palindrome => {
+                let s = digits.format(n);
+                !splits.is_empty() && s.chars().rev().eq(s.chars())
+            }
+        }
+    }
+    #[test]
+    fn policies_match_brute_force() -> Result<()> {
+        let policies: Vec<Policy> = [
+            "exactly:1",
+            "exactly:3",
+            "at-least:1",
+            "at-least:2",
+            "at-most:3",
+            "block:2-3",
+            "palindrome",
+        ]
+        .iter()
+        .map(|policy| policy.parse())
+        .collect::<Result<_>>()?;
+        assert_eq!(policies[5], Policy::BlockLen(2..=3));
+        assert!("at-most:0".parse::<Policy>().is_err());
+        assert!("sometimes".parse::<Policy>().is_err());
+        for digits in [DECIMAL, Digits::new(3, None)?, Digits::new(16, Some(4))?] {
+            for (lo, hi) in [(0, 0), (0, 3000), (9_990, 10_200), (0xfefe, 0x1_0010)] {
+                for policy in &policies {
+                    let ids: Vec<u64> = (lo..=hi)
+                        .filter(|n| brute_force(*n, policy, digits))
+                        .collect();
+                    let checked: Vec<u64> =
+                        (lo..=hi).filter(|n| policy.matches(*n, digits)).collect();
+                    assert_eq!(checked, ids, "{policy:?} {digits:?} {lo} {hi}");
+                    let found = matching(lo, hi, policy, digits, true);
+                    let context = format!("{policy:?} {digits:?} {lo} {hi}");
+                    assert_eq!(found.ids.as_ref(), Some(&ids), "{context}");
+                    assert_eq!(found.count, ids.len() as u128, "{context}");
+                    assert_eq!(
+                        found.sum,
+                        ids.iter().map(|id| *id as u128).sum(),
+                        "{context}"
+                    );
+                    assert_eq!(matching(lo, hi, policy, digits, false).ids, None);
+                }
+            }
+        }
+        let digits = matching(0, 9, &Policy::Palindrome, DECIMAL, true);
+        assert_eq!(digits.ids, Some((0..=9).collect()));
+        // up to 18 digits two lengths share each half length, 19 digits have
+        // 9 * 10^9 and 20 digit ones stop below the half 1844674407
+        let all = matching(1, u64::MAX, &Policy::Palindrome, DECIMAL, false);
+        let below_19 = 2 * (10_u128.pow(9) - 1);
+        assert_eq!(all.count, below_19 + 9 * 10_u128.pow(9) + 844_674_407);
+        Ok(())
+    }
+    #[test]
+    fn multi_period_ids() -> Result<()> {
+        for (id, accepted, rejected) in [
+            (
+                111_111,
+                [
+                    "exactly:2",
+                    "exactly:3",
+                    "at-least:6",
+                    "at-most:3",
+                    "block:2-3",
+                ],
+                ["exactly:4", "at-least:7", "at-most:1", "block:4-5"],
+            ),
+            (
+                12_121_212,
+                [
+                    "exactly:2",
+                    "exactly:4",
+                    "at-least:4",
+                    "at-most:2",
+                    "block:4",
+                ],
+                ["exactly:3", "at-least:5", "at-most:1", "block:1"],
+            ),
+        ] {
+            for (policies, expected) in [(&accepted[..], true), (&rejected[..], false)] {
+                for policy in policies {
+                    let found = matching(id, id, &policy.parse()?, DECIMAL, false);
+                    assert_eq!(found.count == 1, expected, "{id} {policy}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
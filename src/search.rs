@@ -0,0 +1,278 @@
+//! Best-first state space search: breadth-first, Dijkstra and A*.
+//!
+//! States only need `Clone + Eq + Hash`, successors come from a closure. Every
+//! discovered state is stored once in an arena together with its parent, so the
+//! path is rebuilt at the end instead of being carried along in the frontier.
+use anyhow::{Result, bail};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// States taken off the frontier and expanded.
+    pub expanded: usize,
+    /// Successors produced, including ones already seen.
+    pub generated: usize,
+    /// Distinct states discovered.
+    pub visited: usize,
+    pub max_frontier: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found<S, C> {
+    /// States from the start to the goal, both included.
+    pub path: Vec<S>,
+    pub cost: C,
+}
+
+/// Search settings, statistics of the last run are kept for inspection.
+#[derive(Debug, Clone, Default)]
+pub struct Search {
+    node_limit: Option<usize>,
+    stats: SearchStats,
+}
+
+struct Node<S, C> {
+    state: S,
+    parent: Option<usize>,
+    cost: C,
+}
+
+impl Search {
+    /// Fails a search once more than `limit` states have been expanded.
+    pub fn with_node_limit(limit: usize) -> Self {
+        Self {
+            node_limit: Some(limit),
+            ..Self::default()
+        }
+    }
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+    }
+    /// Fewest steps from `start` to a state satisfying `goal`.
+    pub fn bfs<S, I>(
+        &mut self,
+        start: S,
+        mut successors: impl FnMut(&S) -> I,
+        mut goal: impl FnMut(&S) -> bool,
+    ) -> Result<Option<Found<S, usize>>>
+    where
+        S: Clone + Eq + Hash,
+        I: IntoIterator<Item = S>,
+    {
+        self.stats = SearchStats::default();
+        let mut arena = vec![Node {
+            state: start.clone(),
+            parent: None,
+            cost: 0,
+        }];
+        let mut seen = HashMap::from([(start, 0)]);
+        let mut frontier = VecDeque::from([0]);
+        while let Some(idx) = frontier.pop_front() {
+            if goal(&arena[idx].state) {
+                return Ok(Some(self.found(arena, idx, seen.len())));
+            }
+            self.expand()?;
+            let depth = arena[idx].cost + 1;
+            for next in successors(&arena[idx].state) {
+                self.stats.generated += 1;
+                if seen.contains_key(&next) {
+                    continue;
+                }
+                seen.insert(next.clone(), arena.len());
+                frontier.push_back(arena.len());
+                arena.push(Node {
+                    state: next,
+                    parent: Some(idx),
+                    cost: depth,
+                });
+            }
+            self.stats.max_frontier = self.stats.max_frontier.max(frontier.len());
+        }
+        self.stats.visited = seen.len();
+        Ok(None)
+    }
+    /// Cheapest path with non-negative step costs.
+    pub fn dijkstra<S, C, I>(
+        &mut self,
+        start: S,
+        successors: impl FnMut(&S) -> I,
+        goal: impl FnMut(&S) -> bool,
+    ) -> Result<Option<Found<S, C>>>
+    where
+        S: Clone + Eq + Hash,
+        C: Copy + Ord + Add<Output = C> + Default,
+        I: IntoIterator<Item = (S, C)>,
+    {
+        self.astar(start, successors, |_| C::default(), goal)
+    }
+    /// Cheapest path guided by `heuristic`, which must never overestimate the
+    /// remaining cost for the result to be optimal. `C::default()` is zero.
+    pub fn astar<S, C, I>(
+        &mut self,
+        start: S,
+        mut successors: impl FnMut(&S) -> I,
+        mut heuristic: impl FnMut(&S) -> C,
+        mut goal: impl FnMut(&S) -> bool,
+    ) -> Result<Option<Found<S, C>>>
+    where
+        S: Clone + Eq + Hash,
+        C: Copy + Ord + Add<Output = C> + Default,
+        I: IntoIterator<Item = (S, C)>,
+    {
+        self.stats = SearchStats::default();
+        let mut frontier = BinaryHeap::from([Reverse((heuristic(&start), 0))]);
+        let mut arena = vec![Node {
+            state: start.clone(),
+            parent: None,
+            cost: C::default(),
+        }];
+        // arena index of the cheapest known path to every state
+        let mut best = HashMap::from([(start, 0)]);
+        while let Some(Reverse((_, idx))) = frontier.pop() {
+            if best[&arena[idx].state] != idx {
+                continue;
+            }
+            if goal(&arena[idx].state) {
+                return Ok(Some(self.found(arena, idx, best.len())));
+            }
+            self.expand()?;
+            let cost = arena[idx].cost;
+            for (next, step) in successors(&arena[idx].state) {
+                self.stats.generated += 1;
+                let next_cost = cost + step;
+                if best.get(&next).is_some_and(|i| arena[*i].cost <= next_cost) {
+                    continue;
+                }
+                best.insert(next.clone(), arena.len());
+                frontier.push(Reverse((next_cost + heuristic(&next), arena.len())));
+                arena.push(Node {
+                    state: next,
+                    parent: Some(idx),
+                    cost: next_cost,
+                });
+            }
+            self.stats.max_frontier = self.stats.max_frontier.max(frontier.len());
+        }
+        self.stats.visited = best.len();
+        Ok(None)
+    }
+    fn expand(&mut self) -> Result<()> {
+        self.stats.expanded += 1;
+        if let Some(limit) = self.node_limit
+            && self.stats.expanded > limit
+        {
+            bail!("search gave up after expanding {limit} states");
+        }
+        Ok(())
+    }
+    fn found<S, C: Copy>(
+        &mut self,
+        arena: Vec<Node<S, C>>,
+        goal: usize,
+        visited: usize,
+    ) -> Found<S, C> {
+        self.stats.visited = visited;
+        let cost = arena[goal].cost;
+        let mut on_path = vec![false; arena.len()];
+        let mut idx = Some(goal);
+        while let Some(i) = idx {
+            on_path[i] = true;
+            idx = arena[i].parent;
+        }
+        // parents always come before their children in the arena
+        let path = arena
+            .into_iter()
+            .zip(on_path)
+            .filter(|(_, on_path)| *on_path)
+            .map(|(node, _)| node.state)
+            .collect();
+        Found { path, cost }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::IVec2;
+
+    const MAZE: &str = "\
+.....
+.###.
+...#.
+.#.#.
+.#...";
+
+    fn open(p: IVec2) -> bool {
+        (0..5).contains(&p.x)
+            && (0..5).contains(&p.y)
+            && MAZE.lines().nth(p.y as usize).unwrap().as_bytes()[p.x as usize] == b'.'
+    }
+    fn neighbours(p: &IVec2) -> Vec<IVec2> {
+        [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y]
+            .map(|d| *p + d)
+            .into_iter()
+            .filter(|n| open(*n))
+            .collect()
+    }
+
+    #[test]
+    fn all_strategies_agree_on_maze() -> Result<()> {
+        let goal = IVec2::new(4, 4);
+        let mut search = Search::default();
+        let bfs = search
+            .bfs(IVec2::ZERO, neighbours, |p| *p == goal)?
+            .expect("reachable");
+        assert_eq!(bfs.cost, 8);
+        assert_eq!(bfs.path.len(), 9);
+        assert!(
+            bfs.path
+                .windows(2)
+                .all(|w| (w[0] - w[1]).abs().element_sum() == 1)
+        );
+        let weighted = |p: &IVec2| {
+            neighbours(p)
+                .into_iter()
+                .map(|n| (n, 1))
+                .collect::<Vec<_>>()
+        };
+        let dijkstra = search.dijkstra(IVec2::ZERO, weighted, |p| *p == goal)?;
+        assert_eq!(dijkstra.map(|f| f.cost), Some(8));
+        let dijkstra_stats = search.stats();
+        let manhattan = |p: &IVec2| (goal - *p).abs().element_sum();
+        let astar = search.astar(IVec2::ZERO, weighted, manhattan, |p| *p == goal)?;
+        assert_eq!(astar.map(|f| f.cost), Some(8));
+        assert!(search.stats().expanded <= dijkstra_stats.expanded);
+        assert_eq!(search.bfs(IVec2::ZERO, neighbours, |p| p.x > 4)?, None);
+        assert_eq!(search.stats().visited, 18);
+        Ok(())
+    }
+    #[test]
+    fn costs_and_node_limit() -> Result<()> {
+        // the direct edge is one step but more expensive than the detour
+        let edges = |n: &u32| match n {
+            0 => vec![(3, 10), (1, 1)],
+            1 => vec![(2, 1)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        };
+        let mut search = Search::default();
+        let cheapest = search.dijkstra(0, edges, |n| *n == 3)?.expect("reachable");
+        assert_eq!(
+            cheapest,
+            Found {
+                path: vec![0, 1, 2, 3],
+                cost: 3
+            }
+        );
+        let shortest = search.bfs(0, |n| edges(n).into_iter().map(|(m, _)| m), |n| *n == 3)?;
+        assert_eq!(shortest.map(|f| f.path), Some(vec![0, 3]));
+        let mut limited = Search::with_node_limit(100);
+        let endless = limited.bfs(0_u64, |n| [n + 1], |_| false);
+        assert!(endless.is_err());
+        assert_eq!(limited.stats().expanded, 101);
+        Ok(())
+    }
+}
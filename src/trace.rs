@@ -0,0 +1,61 @@
+//! Optional `tracing` instrumentation, built with the `trace` feature.
+//!
+//! Without the feature [`traced!`](crate::traced) is just its body and
+//! [`trace_event!`](crate::trace_event) expands to nothing, so solutions pay
+//! nothing for it. With the feature, `RUST_LOG` picks what gets printed to
+//! stderr, e.g. `RUST_LOG=info` for span timings or `RUST_LOG=day08=debug` for
+//! every circuit merge.
+
+#[cfg(feature = "trace")]
+pub use tracing;
+
+/// Installs a stderr subscriber filtered by `RUST_LOG`, printing closed spans
+/// with their timings. Does nothing without the `trace` feature.
+pub fn init() {
+    #[cfg(feature = "trace")]
+    {
+        use tracing_subscriber::EnvFilter;
+        use tracing_subscriber::fmt::format::FmtSpan;
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::from_default_env())
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(std::io::stderr)
+            .try_init();
+    }
+}
+
+/// Evaluates the expression inside an info level span named by the literal.
+#[macro_export]
+macro_rules! traced {
+    ($name:literal, $body:expr) => {{
+        #[cfg(feature = "trace")]
+        let _span = $crate::trace::tracing::info_span!($name).entered();
+        $body
+    }};
+}
+
+/// Debug level event with a message and `name = value` fields recorded with
+/// their `Debug` format. Disabled, the values are type checked but never evaluated.
+#[macro_export]
+macro_rules! trace_event {
+    ($message:literal $(, $field:ident = $value:expr)* $(,)?) => {{
+        #[cfg(feature = "trace")]
+        $crate::trace::tracing::debug!($($field = ?$value,)* $message);
+        #[cfg(not(feature = "trace"))]
+        if false {
+            $(let _ = &$value;)*
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn macros_keep_their_values() {
+        super::init();
+        let answer = crate::traced!("part1", 6 * 7);
+        let merged = [1, 2];
+        crate::trace_event!("merge", a = merged[0], b = merged[1]);
+        assert_eq!(answer, 42);
+    }
+}